@@ -1,5 +1,60 @@
 #![allow(unused, dead_code)]
 
+/// A pool of its own for every test, each `#[tokio::test]` runs on its own
+/// runtime and the connections of the shared pool are bound to the first one.
+#[cfg(test)]
+async fn test_pool() -> sqlx::PgPool {
+    sqlx::PgPool::connect(&std::env::var("DATABASE_URL").expect("DATABASE_URL must be set"))
+        .await
+        .unwrap()
+}
+
+#[cfg(test)]
+use crate::{query::*, *};
+
+// The customer and order tables of the test database, for the modules that
+// only need some table to query.
+#[cfg(test)]
+#[derive(Table)]
+#[name = "public.customer"]
+pub struct Customer {
+    id: Identity<PrimaryKey<Self, i32>>,
+    first_name: String,
+    last_name: String,
+    email: Unique<String>,
+    phone_number: Option<Unique<String>>,
+    #[gild(default)]
+    created_at: chrono::NaiveDateTime,
+    #[gild(default)]
+    updated_at: chrono::NaiveDateTime,
+}
+
+#[cfg(test)]
+#[derive(Table)]
+#[name = "public.order"]
+pub struct Order {
+    id: Identity<PrimaryKey<Self, i32>>,
+    customer_id: Option<ForeignKey<Customer, 1, i32>>,
+    name: String,
+    status: String,
+    total_amount: f64,
+    quantity: Option<i32>,
+    note: Option<String>,
+    test: bool,
+    #[gild(default)]
+    created_at: chrono::NaiveDateTime,
+}
+
+#[cfg(test)]
+fn customers() -> QuerySet<Customer> {
+    QuerySet::new(QueryState::new_select())
+}
+
+#[cfg(test)]
+fn orders() -> QuerySet<Order> {
+    QuerySet::new(QueryState::new_select())
+}
+
 #[cfg(test)]
 mod table_macro {
     use crate::*;
@@ -93,19 +148,39 @@ mod table_macro {
 
     #[tokio::test]
     async fn expand_unique_getter() {
+        let pool = super::test_pool().await;
+
         // Unique<T>
-        let method_exists = Customer::get_by_email("me@company.com").await.expect("");
+        let method_exists = Customer::get_by_email(&pool, "me@company.com")
+            .await
+            .expect("");
         assert!(method_exists.is_some() || method_exists.is_none());
     }
 
     #[tokio::test]
     async fn expand_option_unique_getter() {
-        let method_exists2 = Customer::get_by_phone_number("me@company.com".to_string())
+        let pool = super::test_pool().await;
+
+        let method_exists2 = Customer::get_by_phone_number(&pool, "me@company.com".to_string())
             .await
             .expect("");
         assert!(method_exists2.is_some() || method_exists2.is_none())
     }
 
+    #[tokio::test]
+    async fn getters_run_on_any_executor() {
+        let pool = super::test_pool().await;
+
+        let mut conn = pool.acquire().await.unwrap();
+        let on_conn = Customer::get_by_email(&mut *conn, "me@company.com").await;
+        assert!(on_conn.is_ok());
+
+        let mut tx = pool.begin().await.unwrap();
+        let in_tx = Customer::get_by_email(&mut tx, "me@company.com").await;
+        assert!(in_tx.is_ok());
+        tx.rollback().await.unwrap();
+    }
+
     // TODO: support trimming {table_name}_id ie order_id -> id
     // for the rust code
 }
@@ -148,13 +223,553 @@ mod query_builder {
         q.limit = Some(1);
         q.offset = Some(0);
         // q.set_select(["name".into(), "id".into()]);
-        q.where_conditions.push(Where::neq("id", "4".into()));
+        q.and_where(Where::neq("id", 4));
+
+        assert_eq!(
+            q.to_string(),
+            "SELECT \"id\", \"customer_id\", \"order_date\", \"total_amount\", \"status\", \"name\", \"shipping_address\", \"created_at\", \"updated_at\" FROM \"public\".\"order\" WHERE \"id\" != $1 LIMIT 1 OFFSET 0;".to_string()
+        );
+        assert_eq!(q.to_sql().1, vec![SqlValue::Integer(4)]);
+    }
+
+    #[test]
+    fn reserved_and_schema_qualified_names_are_quoted() {
+        #[derive(Table)]
+        #[name = "private.group"]
+        pub struct Group {
+            id: PrimaryKey<Self, i32>,
+            name: String,
+        }
+
+        let q: QueryState<Group> = QueryState::new_select();
+
+        assert_eq!(
+            q.to_string(),
+            "SELECT \"id\", \"name\" FROM \"private\".\"group\";"
+        );
+    }
+
+    #[test]
+    fn values_are_bound_not_interpolated() {
+        let mut q: QueryState<Customer> = QueryState::new_select();
+        q.and_where(Where::eq("email", "x' OR '1'='1"));
+        q.and_where(Where::like("first_name", "%jo%"));
+
+        let (sql, values) = q.to_sql();
+
+        assert_eq!(
+            sql,
+            "SELECT \"id\", \"first_name\", \"last_name\", \"email\", \"phone_number\", \"created_at\", \"updated_at\" FROM \"public\".\"customer\" WHERE \"email\" = $1 AND \"first_name\" LIKE $2;"
+        );
+        assert_eq!(
+            values,
+            vec![
+                SqlValue::Text("x' OR '1'='1".to_string()),
+                SqlValue::Text("%jo%".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn renamed_columns_use_database_names() {
+        #[derive(Table)]
+        #[name = "public.account"]
+        pub struct Account {
+            #[gild(column = "account_id")]
+            id: PrimaryKey<Self, i32>,
+            #[gild(column = "display_name")]
+            name: String,
+            #[gild(column = "balance_cents", default)]
+            balance: i64,
+        }
+
+        let q = QuerySet::new(QueryState::<Account>::new_select())
+            .filter(|a| a.name.starts_with("A") && a.balance > 0)
+            .order_by_desc(|a| a.balance);
+
+        assert_eq!(
+            q.to_sql().0,
+            "SELECT \"account_id\", \"display_name\", \"balance_cents\" FROM \"public\".\"account\" WHERE \"display_name\" LIKE $1 AND \"balance_cents\" > $2 ORDER BY \"balance_cents\" DESC;"
+        );
+        assert_eq!(Account::primary_key_column(), "account_id");
+        assert_eq!(Account::insert_columns(), &["account_id", "display_name"]);
+        assert_eq!(Account::ordinal("display_name"), Some(2));
+    }
+}
+
+#[cfg(test)]
+mod order_by {
+    use super::customers;
+    use crate::{query::*, *};
+
+    #[test]
+    fn order_after_where() {
+        let q = customers()
+            .filter(|c| c.first_name == "John")
+            .order_by_desc(|c| c.created_at);
+
+        assert_eq!(
+            q.to_string(),
+            "SELECT \"id\", \"first_name\", \"last_name\", \"email\", \"phone_number\", \"created_at\", \"updated_at\" FROM \"public\".\"customer\" WHERE \"first_name\" = $1 ORDER BY \"created_at\" DESC;"
+        );
+    }
+
+    #[test]
+    fn then_by_with_nulls() {
+        let q = customers()
+            .order_by_asc(|c| c.last_name)
+            .then_by(|c| c.first_name)
+            .then_by_desc(|c| c.phone_number)
+            .nulls_last();
+
+        assert_eq!(
+            q.to_string(),
+            "SELECT \"id\", \"first_name\", \"last_name\", \"email\", \"phone_number\", \"created_at\", \"updated_at\" FROM \"public\".\"customer\" ORDER BY \"last_name\" ASC, \"first_name\" ASC, \"phone_number\" DESC NULLS LAST;"
+        );
+    }
+
+    #[test]
+    fn order_by_replaces_previous_ordering() {
+        let q = customers()
+            .order_by_asc(|c| c.last_name)
+            .nulls_first()
+            .order_by_desc(|c| c.id);
+
+        assert_eq!(
+            q.to_string(),
+            "SELECT \"id\", \"first_name\", \"last_name\", \"email\", \"phone_number\", \"created_at\", \"updated_at\" FROM \"public\".\"customer\" ORDER BY \"id\" DESC;"
+        );
+    }
+}
+
+#[cfg(test)]
+mod joins {
+    use super::{Customer, Order, orders};
+    use crate::{query::*, *};
+
+    #[derive(Table)]
+    #[name = "public.region"]
+    pub struct Region {
+        id: PrimaryKey<Self, i32>,
+        name: String,
+    }
+
+    // two tables referencing each other
+    #[derive(Table)]
+    #[name = "public.team"]
+    pub struct Team {
+        id: PrimaryKey<Self, i32>,
+        captain_id: ForeignKey<Player, 1, i32>,
+    }
+
+    #[derive(Table)]
+    #[name = "public.player"]
+    pub struct Player {
+        id: PrimaryKey<Self, i32>,
+        team_id: ForeignKey<Team, 1, i32>,
+    }
+
+    // more than one foreign key to the same table
+    #[derive(Table)]
+    #[name = "public.invoice"]
+    pub struct Invoice {
+        id: PrimaryKey<Self, i32>,
+        billing_customer_id: ForeignKey<Customer, 1, i32>,
+        shipping_customer_id: ForeignKey<Customer, 1, i32>,
+    }
+
+    #[derive(Table)]
+    #[name = "public.employee"]
+    pub struct Employee {
+        id: PrimaryKey<Self, i32>,
+        manager_id: Option<ForeignKey<Employee, 1, i32>>,
+    }
+
+    #[derive(sqlx::FromRow)]
+    pub struct OrderEmail {
+        name: String,
+        email: String,
+    }
+
+    #[test]
+    fn join_inferred_from_foreign_key() {
+        let q = orders().inner_join::<Customer, _>();
+
+        assert_eq!(
+            q.to_string(),
+            "SELECT \"public\".\"order\".\"id\", \"public\".\"order\".\"customer_id\", \"public\".\"order\".\"name\", \"public\".\"order\".\"status\", \"public\".\"order\".\"total_amount\", \"public\".\"order\".\"quantity\", \"public\".\"order\".\"note\", \"public\".\"order\".\"test\", \"public\".\"order\".\"created_at\" FROM \"public\".\"order\" INNER JOIN \"public\".\"customer\" ON \"public\".\"order\".\"customer_id\" = \"public\".\"customer\".\"id\";"
+        );
+
+        // the other side of the foreign key joins the same columns
+        let q: QuerySet<Customer> = QuerySet::new(QueryState::new_select()).left_join::<Order, _>();
+        assert!(q.to_string().ends_with(
+            "FROM \"public\".\"customer\" LEFT JOIN \"public\".\"order\" ON \"public\".\"customer\".\"id\" = \"public\".\"order\".\"customer_id\";"
+        ));
+    }
+
+    #[test]
+    fn mutual_foreign_keys_name_the_side() {
+        let teams = || QuerySet::new(QueryState::<Team>::new_select());
+
+        assert!(
+            teams()
+                .inner_join::<Player, OwnForeignKey>()
+                .to_string()
+                .ends_with("ON \"public\".\"team\".\"captain_id\" = \"public\".\"player\".\"id\";")
+        );
+        assert!(
+            teams()
+                .inner_join::<Player, TheirForeignKey>()
+                .to_string()
+                .ends_with("ON \"public\".\"team\".\"id\" = \"public\".\"player\".\"team_id\";")
+        );
+    }
+
+    #[test]
+    fn several_foreign_keys_join_on_explicit_columns() {
+        let q = QuerySet::new(QueryState::<Invoice>::new_select())
+            .join_on::<Customer, _, _>(JoinType::Inner, |i, c| (i.shipping_customer_id, c.id));
+
+        assert!(q.to_string().ends_with(
+            "INNER JOIN \"public\".\"customer\" ON \"public\".\"invoice\".\"shipping_customer_id\" = \"public\".\"customer\".\"id\";"
+        ));
+    }
+
+    #[test]
+    fn join_on_explicit_columns() {
+        let q = orders().join_on::<Region, _, _>(JoinType::Full, |o, r| (o.name, r.name));
+
+        assert!(q.to_string().ends_with(
+            "FROM \"public\".\"order\" FULL JOIN \"public\".\"region\" ON \"public\".\"order\".\"name\" = \"public\".\"region\".\"name\";"
+        ));
+    }
+
+    #[test]
+    fn cross_join_has_no_on() {
+        let q = orders().cross_join::<Region>();
+
+        assert!(
+            q.to_string()
+                .ends_with("FROM \"public\".\"order\" CROSS JOIN \"public\".\"region\";")
+        );
+    }
+
+    #[test]
+    fn joined_columns_are_qualified_everywhere() {
+        let q = orders()
+            .inner_join::<Customer, _>()
+            .filter(|o| o.name == "test")
+            .order_by_desc(|o| o.id)
+            .select_joined::<Customer, _>(|o, c| (o.name, c.email));
+
+        let (sql, values) = q.to_sql();
+        assert_eq!(
+            sql,
+            "SELECT \"public\".\"order\".\"name\", \"public\".\"customer\".\"email\" FROM \"public\".\"order\" INNER JOIN \"public\".\"customer\" ON \"public\".\"order\".\"customer_id\" = \"public\".\"customer\".\"id\" WHERE \"public\".\"order\".\"name\" = $1 ORDER BY \"public\".\"order\".\"id\" DESC;"
+        );
+        assert_eq!(values, vec![SqlValue::Text("test".to_string())]);
+    }
+
+    #[test]
+    fn joined_rows_decode_into_tuples_or_structs() {
+        // only checks the row types line up, nothing is executed
+        let _: QuerySet<Order, (String, Unique<String>)> =
+            orders()
+                .inner_join::<Customer, _>()
+                .select_joined::<Customer, _>(|o, c| (o.name, c.email));
+
+        let _: QuerySet<Order, OrderEmail> = orders()
+            .inner_join::<Customer, _>()
+            .select_joined::<Customer, _>(|o, c| (o.name, c.email))
+            .decode_as::<OrderEmail>();
+    }
+}
+
+#[cfg(test)]
+mod aggregates {
+    use super::{Order, orders};
+    use crate::{query::*, *};
+
+    #[test]
+    fn group_by_with_aggregates() {
+        let q = orders().group_by(|o| (o.status,)).aggregate(|o| {
+            (
+                o.status,
+                o.id.count(),
+                o.total_amount.sum(),
+                o.quantity.avg(),
+                o.quantity.max(),
+            )
+        });
+
+        assert_eq!(
+            q.to_string(),
+            "SELECT \"status\", count(\"id\"), sum(\"total_amount\")::double precision, avg(\"quantity\")::double precision, max(\"quantity\") FROM \"public\".\"order\" GROUP BY \"status\";"
+        );
+    }
+
+    #[test]
+    fn having_comes_after_where_and_group_by() {
+        let q = orders()
+            .filter(|o| !o.test)
+            .group_by(|o| (o.customer_id, o.status))
+            .having(|o| o.id.count() > 2 && !(o.total_amount.sum() >= 100.0))
+            .order_by_asc(|o| o.customer_id)
+            .aggregate(|o| (o.customer_id, o.status, o.id.count()));
+
+        let (sql, values) = q.to_sql();
+        assert_eq!(
+            sql,
+            "SELECT \"customer_id\", \"status\", count(\"id\") FROM \"public\".\"order\" WHERE \"test\" != $1 GROUP BY \"customer_id\", \"status\" HAVING count(\"id\") > $2 AND sum(\"total_amount\")::double precision < $3 ORDER BY \"customer_id\" ASC;"
+        );
+        assert_eq!(
+            values,
+            vec![
+                SqlValue::Bool(true),
+                SqlValue::BigInt(2),
+                SqlValue::Double(100.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn aggregates_decode_into_typed_rows() {
+        let _: QuerySet<Order, (String, i64)> = orders()
+            .group_by(|o| (o.status,))
+            .aggregate(|o| (o.status, o.id.count()));
+
+        let _: QuerySet<Order, (i64, Option<f64>, Option<f64>)> =
+            orders().aggregate(|o| (o.id.count(), o.total_amount.avg(), o.total_amount.min()));
+    }
+}
+
+#[cfg(test)]
+mod insert {
+    use super::Customer;
+    use crate::{query::*, *};
+
+    #[derive(Table)]
+    #[name = "public.visit"]
+    pub struct Visit {
+        id: Identity<i64>,
+        #[gild(default)]
+        at: chrono::NaiveDateTime,
+    }
+
+    fn customer(first_name: &str, phone_number: Option<&str>) -> Customer {
+        Customer {
+            id: PrimaryKey::from(0).into(),
+            first_name: first_name.to_string(),
+            last_name: "Lee".to_string(),
+            email: format!("{first_name}@example.com").into(),
+            phone_number: phone_number.map(|p| p.to_string().into()),
+            created_at: chrono::NaiveDateTime::default(),
+            updated_at: chrono::NaiveDateTime::default(),
+        }
+    }
+
+    #[test]
+    fn identity_and_default_columns_are_skipped() {
+        assert_eq!(
+            Customer::insert_columns(),
+            &["first_name", "last_name", "email", "phone_number"]
+        );
+        assert_eq!(Visit::insert_columns(), &[] as &[&str]);
+    }
+
+    #[test]
+    fn insert_binds_every_row() {
+        let rows = [customer("jo", Some("555")), customer("al", None)];
+        let q: QueryState<Customer> =
+            QueryState::new_insert(rows.iter().map(Insertable::insert_values).collect());
+
+        let (sql, values) = q.to_sql();
+        assert_eq!(
+            sql,
+            "INSERT INTO \"public\".\"customer\" (\"first_name\", \"last_name\", \"email\", \"phone_number\") VALUES ($1, $2, $3, $4), ($5, $6, $7, $8) RETURNING \"id\", \"first_name\", \"last_name\", \"email\", \"phone_number\", \"created_at\", \"updated_at\";"
+        );
+        assert_eq!(
+            values,
+            vec![
+                SqlValue::Text("jo".to_string()),
+                SqlValue::Text("Lee".to_string()),
+                SqlValue::Text("jo@example.com".to_string()),
+                SqlValue::Text("555".to_string()),
+                SqlValue::Text("al".to_string()),
+                SqlValue::Text("Lee".to_string()),
+                SqlValue::Text("al@example.com".to_string()),
+                SqlValue::Null,
+            ]
+        );
+    }
 
+    #[test]
+    fn insert_without_columns_uses_defaults() {
+        let q: QueryState<Visit> = QueryState::new_insert(vec![Vec::new()]);
         assert_eq!(
             q.to_string(),
-            "SELECT id, customer_id, order_date, total_amount, status, name, shipping_address, created_at, updated_at FROM order WHERE id != 4 LIMIT 1 OFFSET 0;".to_string()
+            "INSERT INTO \"public\".\"visit\" DEFAULT VALUES RETURNING \"id\", \"at\";"
+        );
+    }
+}
+
+#[cfg(test)]
+mod update {
+    use super::{Customer, Order, orders};
+    use crate::{query::*, *};
+
+    #[test]
+    fn set_values_come_before_filter_values() {
+        let q = orders()
+            .filter(|o| o.status == "paid")
+            .into_update(|o| {
+                o.status.set("shipped");
+                o.note.set(None);
+            })
+            .unwrap();
+
+        let (sql, values) = q.to_sql();
+        assert_eq!(
+            sql,
+            "UPDATE \"public\".\"order\" SET \"status\" = $1, \"note\" = $2 WHERE \"status\" = $3;"
+        );
+        assert_eq!(
+            values,
+            vec![
+                SqlValue::Text("shipped".to_string()),
+                SqlValue::Null,
+                SqlValue::Text("paid".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn clauses_an_update_cannot_carry_are_refused() {
+        let refused = |q: QuerySet<Order>| match q.into_update(|o| o.status.set("x")) {
+            Err(OrmoluError::UnsupportedClause {
+                statement: "update",
+                table: "public.order",
+                clause,
+            }) => clause,
+            other => panic!(
+                "expected an unsupported clause, got {:?}",
+                other.map(|q| q.to_string())
+            ),
+        };
+        let paid = || orders().filter(|o| o.status == "paid");
+
+        assert_eq!(refused(paid().order_by_asc(|o| o.id)), "ORDER BY");
+        assert_eq!(refused(paid().limit(10)), "LIMIT");
+        assert_eq!(refused(paid().offset(10)), "OFFSET");
+        assert_eq!(refused(paid().inner_join::<Customer, _>()), "a join");
+    }
+
+    #[test]
+    fn model_update_writes_every_column_but_the_key() {
+        assert_eq!(Order::primary_key_column(), "id");
+        // defaulted columns are written back, only inserts leave them out
+        assert_eq!(
+            Order::update_columns(),
+            &[
+                "customer_id",
+                "name",
+                "status",
+                "total_amount",
+                "quantity",
+                "note",
+                "test",
+                "created_at"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn nothing_set_updates_nothing() {
+        // nothing is sent, so the pool never connects
+        let pool = sqlx::PgPool::connect_lazy("postgres://localhost").unwrap();
+        assert_eq!(orders().all_rows().update(&pool, |_| {}).await.unwrap(), 0);
+    }
+}
+
+#[cfg(test)]
+mod delete {
+    use super::{Customer, Order, orders};
+    use crate::{query::*, *};
+
+    fn is_unfiltered<T>(result: Result<T, OrmoluError>, expected: &str) -> bool {
+        matches!(
+            result,
+            Err(OrmoluError::Unfiltered { statement, table: "public.order" }) if statement == expected
         )
     }
+
+    #[test]
+    fn filtered_delete() {
+        let q = orders().filter(|o| o.test == true).into_delete().unwrap();
+        let (sql, values) = q.to_sql();
+
+        assert_eq!(sql, "DELETE FROM \"public\".\"order\" WHERE \"test\" = $1;");
+        assert_eq!(values, vec![SqlValue::Bool(true)]);
+    }
+
+    #[test]
+    #[allow(clippy::overly_complex_bool_expr)]
+    fn unfiltered_writes_are_refused() {
+        assert!(is_unfiltered(orders().into_delete(), "delete"));
+        assert!(is_unfiltered(
+            orders().into_update(|o| o.status.set("x")),
+            "update"
+        ));
+
+        // closures that match every row are no better than no filter
+        assert!(is_unfiltered(
+            orders().filter(|_| true).into_delete(),
+            "delete"
+        ));
+        assert!(is_unfiltered(
+            orders().filter(|o| o.test == true || true).into_delete(),
+            "delete"
+        ));
+    }
+
+    #[test]
+    fn clauses_a_delete_cannot_carry_are_refused() {
+        let refused = |q: QuerySet<Order>| match q.into_delete() {
+            Err(OrmoluError::UnsupportedClause {
+                statement: "delete",
+                table: "public.order",
+                clause,
+            }) => clause,
+            other => panic!(
+                "expected an unsupported clause, got {:?}",
+                other.map(|q| q.to_string())
+            ),
+        };
+        let test = || orders().filter(|o| o.test == true);
+
+        assert_eq!(refused(test().order_by_desc(|o| o.created_at)), "ORDER BY");
+        assert_eq!(refused(test().limit(10)), "LIMIT");
+        assert_eq!(refused(test().offset(10)), "OFFSET");
+        assert_eq!(refused(test().left_join::<Customer, _>()), "a join");
+
+        // all_rows() opts into an unfiltered delete, not into a limited one
+        assert_eq!(refused(orders().all_rows().limit(1)), "LIMIT");
+    }
+
+    #[test]
+    fn all_rows_opts_in() {
+        let q = orders().all_rows().into_delete().unwrap();
+        assert_eq!(q.to_string(), "DELETE FROM \"public\".\"order\";");
+
+        let q = orders()
+            .all_rows()
+            .into_update(|o| o.status.set("archived"))
+            .unwrap();
+        assert_eq!(
+            q.to_string(),
+            "UPDATE \"public\".\"order\" SET \"status\" = $1;"
+        );
+    }
 }
 
 #[cfg(test)]
@@ -190,6 +805,16 @@ mod filter_proxy_iter_dsl {
         updated_at: chrono::NaiveDateTime,
     }
 
+    #[derive(Table)]
+    #[name = "public.shipment"]
+    pub struct Shipment {
+        id: Identity<PrimaryKey<Self, i64>>,
+        order_id: ForeignKey<Order, 1, i32>,
+        tracking_code: Unique<VarChar<32>>,
+        weight: f32,
+        shipped_at: chrono::NaiveDateTime,
+    }
+
     #[test]
     fn multiple_field_filter_with_or() {
         let state: QueryState<Order> = QueryState::new_select();
@@ -198,9 +823,9 @@ mod filter_proxy_iter_dsl {
             // TODO: Can we be more clever with order.test. Possibly just return
             // a bool instead of Field<bool>
             .filter(|order| !order.name.contains("test") || !order.test)
-            .filter(|order| order.id != PrimaryKey::from(2) && order.name.contains("john"));
+            .filter(|order| order.id != 2 && order.name.contains("john"));
 
-        assert_eq!(f.to_string(), "SELECT id, customer_id, order_date, total_amount, status, name, test, shipping_address, created_at, updated_at FROM order WHERE name NOT LIKE %test% AND id != 2 AND name LIKE %john%;".to_string())
+        assert_eq!(f.to_string(), "SELECT \"id\", \"customer_id\", \"order_date\", \"total_amount\", \"status\", \"test\", \"name\", \"shipping_address\", \"created_at\", \"updated_at\" FROM \"public\".\"order\" WHERE (\"name\" NOT LIKE $1 OR \"test\" != $2) AND \"id\" != $3 AND \"name\" LIKE $4;".to_string())
     }
 
     #[test]
@@ -237,10 +862,9 @@ mod filter_proxy_iter_dsl {
         let orders = QuerySet::new(state);
         let f = orders
             .filter(|order| !order.name.contains("test"))
-            .filter(|order| order.id != 2.into() && order.name.contains("john"));
+            .filter(|order| order.id != 2 && order.name.contains("john"));
 
-        // TODO: fix the 2.into() above ^
-        assert_eq!(f.to_string(), "SELECT id, customer_id, order_date, total_amount, status, name, shipping_address, created_at, updated_at FROM order WHERE name NOT LIKE %test% AND id != 2 AND name LIKE %john%;".to_string())
+        assert_eq!(f.to_string(), "SELECT \"id\", \"customer_id\", \"order_date\", \"total_amount\", \"status\", \"name\", \"shipping_address\", \"created_at\", \"updated_at\" FROM \"public\".\"order\" WHERE \"name\" NOT LIKE $1 AND \"id\" != $2 AND \"name\" LIKE $3;".to_string())
     }
 
     #[test]
@@ -292,18 +916,374 @@ mod filter_proxy_iter_dsl {
         let state: QueryState<Order> = QueryState::new_select();
         let orders = QuerySet::new(state);
         let f = orders.filter(|order| {
-            !order.name.contains("test")
-                && order.id != PrimaryKey::from(2)
-                && order.name.contains("john")
+            !order.name.contains("test") && order.id != 2 && order.name.contains("john")
         });
 
-        assert_eq!(f.to_string(), "SELECT id, customer_id, order_date, total_amount, status, name, shipping_address, created_at, updated_at FROM order WHERE name NOT LIKE %test% AND id != 2 AND name LIKE %john%;".to_string())
+        assert_eq!(f.to_string(), "SELECT \"id\", \"customer_id\", \"order_date\", \"total_amount\", \"status\", \"name\", \"shipping_address\", \"created_at\", \"updated_at\" FROM \"public\".\"order\" WHERE \"name\" NOT LIKE $1 AND \"id\" != $2 AND \"name\" LIKE $3;".to_string())
     }
 
-    fn normal_filter() {
-        let names = vec![
-            String::from("Alice"),
-            String::from("Bob"),
+    #[test]
+    fn query_set_or_groups_each_side() {
+        let lhs = QuerySet::new(QueryState::<Order>::new_select())
+            .filter(|order| order.name.contains("a") && order.name.contains("b"));
+        let rhs = QuerySet::new(QueryState::<Order>::new_select())
+            .filter(|order| order.name.contains("c"));
+
+        let f = lhs.or(rhs).filter(|order| !order.test);
+
+        assert_eq!(f.to_string(), "SELECT \"id\", \"customer_id\", \"order_date\", \"total_amount\", \"status\", \"test\", \"name\", \"shipping_address\", \"created_at\", \"updated_at\" FROM \"public\".\"order\" WHERE ((\"name\" LIKE $1 AND \"name\" LIKE $2) OR \"name\" LIKE $3) AND \"test\" != $4;".to_string())
+    }
+
+    #[test]
+    fn filter_or_extends_existing_filters() {
+        let f = QuerySet::new(QueryState::<Order>::new_select())
+            .filter(|order| order.name.contains("a"))
+            .filter_or(|order| order.test == true);
+
+        assert_eq!(f.to_string(), "SELECT \"id\", \"customer_id\", \"order_date\", \"total_amount\", \"status\", \"test\", \"name\", \"shipping_address\", \"created_at\", \"updated_at\" FROM \"public\".\"order\" WHERE \"name\" LIKE $1 OR \"test\" = $2;".to_string())
+    }
+
+    #[test]
+    fn filter_with_q_built_outside_closure() {
+        let shipped = Q::from(Where::eq("status", "shipped")) | Where::eq("status", "delivered");
+
+        let f = QuerySet::new(QueryState::<Order>::new_select())
+            .filter_q(!shipped)
+            .filter(|order| order.name.contains("john"));
+
+        let (sql, values) = f.to_sql();
+        assert_eq!(
+            sql,
+            "SELECT \"id\", \"customer_id\", \"order_date\", \"total_amount\", \"status\", \"test\", \"name\", \"shipping_address\", \"created_at\", \"updated_at\" FROM \"public\".\"order\" WHERE NOT (\"status\" = $1 OR \"status\" = $2) AND \"name\" LIKE $3;"
+        );
+        assert_eq!(
+            values,
+            vec![
+                SqlValue::Text("shipped".into()),
+                SqlValue::Text("delivered".into()),
+                SqlValue::Text("%john%".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn ordering_comparisons() {
+        let since = chrono::NaiveDate::from_ymd_opt(2024, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+
+        let f = QuerySet::new(QueryState::<Order>::new_select()).filter(|order| {
+            order.total_amount > 100.0
+                && order.order_date >= since
+                && order.name < "m"
+                && order.id <= 50
+        });
+
+        let (sql, values) = f.to_sql();
+        assert_eq!(
+            sql,
+            "SELECT \"id\", \"customer_id\", \"order_date\", \"total_amount\", \"status\", \"test\", \"name\", \"shipping_address\", \"created_at\", \"updated_at\" FROM \"public\".\"order\" WHERE \"total_amount\" > $1 AND \"order_date\" >= $2 AND \"name\" < $3 AND \"id\" <= $4;"
+        );
+        assert_eq!(
+            values,
+            vec![
+                SqlValue::Double(100.0),
+                SqlValue::Timestamp(since),
+                SqlValue::Text("m".into()),
+                SqlValue::Integer(50)
+            ]
+        );
+    }
+
+    #[test]
+    fn wrapped_columns_compare_to_inner_values() {
+        let f = QuerySet::new(QueryState::<Shipment>::new_select()).filter(|s| {
+            s.id > 10 && s.order_id == 4 && s.tracking_code == "1Z999" && s.weight < 2.5
+        });
+
+        let (sql, values) = f.to_sql();
+        assert_eq!(
+            sql,
+            "SELECT \"id\", \"order_id\", \"tracking_code\", \"weight\", \"shipped_at\" FROM \"public\".\"shipment\" WHERE \"id\" > $1 AND \"order_id\" = $2 AND \"tracking_code\" = $3 AND \"weight\" < $4;"
+        );
+        assert_eq!(
+            values,
+            vec![
+                SqlValue::BigInt(10),
+                SqlValue::Integer(4),
+                SqlValue::Text("1Z999".into()),
+                SqlValue::Real(2.5)
+            ]
+        );
+    }
+
+    #[test]
+    fn nullable_columns() {
+        let customers = || QuerySet::new(QueryState::<Customer>::new_select());
+        let where_clause = |q: QuerySet<Customer>| {
+            let (sql, _) = q.to_sql();
+            sql.split_once(" WHERE ").unwrap().1.to_string()
+        };
+
+        assert_eq!(
+            where_clause(customers().filter(|c| {
+                c.phone_number.is_null() || c.phone_number == Some("555".to_string())
+            })),
+            "\"phone_number\" IS NULL OR \"phone_number\" = $1;"
+        );
+        assert_eq!(
+            where_clause(customers().filter(|c| c.phone_number != None)),
+            "\"phone_number\" IS NOT NULL;"
+        );
+        assert_eq!(
+            where_clause(customers().filter(|c| !c.phone_number.is_not_null())),
+            "\"phone_number\" IS NULL;"
+        );
+        // rows without a phone number are not equal to "555" either
+        assert_eq!(
+            where_clause(customers().filter(|c| c.phone_number != Some("555".to_string()))),
+            "\"phone_number\" IS DISTINCT FROM $1;"
+        );
+    }
+
+    #[test]
+    #[allow(clippy::nonminimal_bool, clippy::neg_cmp_op_on_partial_ord)] // the negations are spelled out on purpose
+    fn nullable_ordering() {
+        let customers = || QuerySet::new(QueryState::<Customer>::new_select());
+        let where_clause = |q: QuerySet<Customer>| {
+            let (sql, _) = q.to_sql();
+            sql.split_once(" WHERE ").unwrap().1.to_string()
+        };
+
+        assert_eq!(
+            where_clause(
+                customers().filter(|c| c.phone_number > "555" && c.phone_number <= c.email)
+            ),
+            "\"phone_number\" > $1 AND \"phone_number\" <= \"email\";"
+        );
+        // a NULL phone number is not greater than "555" either
+        assert_eq!(
+            where_clause(customers().filter(|c| !(c.phone_number > "555"))),
+            "(\"phone_number\" <= $1 OR \"phone_number\" IS NULL);"
+        );
+        assert_eq!(
+            where_clause(
+                customers().filter(|c| !(c.phone_number >= "555") || !(c.phone_number < "999"))
+            ),
+            "(\"phone_number\" < $1 OR \"phone_number\" IS NULL) OR (\"phone_number\" >= $2 OR \"phone_number\" IS NULL);"
+        );
+    }
+
+    #[test]
+    fn string_matching_escapes_wildcards() {
+        let f = QuerySet::new(QueryState::<Customer>::new_select()).filter(|c| {
+            c.first_name.starts_with("50%_off")
+                && c.email.ends_with("@x.io")
+                && c.last_name.icontains("o\\n")
+        });
+
+        let (sql, values) = f.to_sql();
+        assert!(sql.ends_with(
+            "WHERE \"first_name\" LIKE $1 AND \"email\" LIKE $2 AND \"last_name\" ILIKE $3;"
+        ));
+        assert_eq!(
+            values,
+            vec![
+                SqlValue::Text("50\\%\\_off%".into()),
+                SqlValue::Text("%@x.io".into()),
+                SqlValue::Text("%o\\\\n%".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn regex_and_similar_to() {
+        #[derive(Table)]
+        #[name = "public.airport"]
+        pub struct Airport {
+            code: Char<3>,
+            name: VarChar<64>,
+            city: Option<String>,
+        }
+
+        let f = QuerySet::new(QueryState::<Airport>::new_select()).filter(|a| {
+            a.code.similar_to("(LH|LG)%") && (a.name.regex("^San ") || !a.city.iregex("york$"))
+        });
+
+        let (sql, values) = f.to_sql();
+        assert_eq!(
+            sql,
+            "SELECT \"code\", \"name\", \"city\" FROM \"public\".\"airport\" WHERE \"code\" SIMILAR TO $1 AND (\"name\" ~ $2 OR (\"city\" !~* $3 OR \"city\" IS NULL));"
+        );
+        assert_eq!(
+            values,
+            vec![
+                SqlValue::Text("(LH|LG)%".into()),
+                SqlValue::Text("^San ".into()),
+                SqlValue::Text("york$".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn membership_in_lists() {
+        let statuses = vec!["cancelled".to_string(), "refunded".to_string()];
+        let f = QuerySet::new(QueryState::<Order>::new_select())
+            .filter(|o| o.id.is_in(&[1, 2, 3]) && !o.status.is_in(&statuses));
+
+        let (sql, values) = f.to_sql();
+        assert!(sql.ends_with("WHERE \"id\" = ANY($1) AND \"status\" <> ALL($2);"));
+        assert_eq!(
+            values,
+            vec![
+                SqlValue::Array(vec![
+                    SqlValue::Integer(1),
+                    SqlValue::Integer(2),
+                    SqlValue::Integer(3)
+                ]),
+                SqlValue::Array(vec![
+                    SqlValue::Text("cancelled".into()),
+                    SqlValue::Text("refunded".into())
+                ])
+            ]
+        );
+
+        let none: [i32; 0] = [];
+        let f = QuerySet::new(QueryState::<Order>::new_select()).filter(|o| o.id.is_in(&none));
+        assert!(f.to_sql().0.ends_with("WHERE FALSE;"));
+    }
+
+    #[test]
+    fn lists_that_cannot_be_bound_are_refused() {
+        let refused = |value: SqlValue| {
+            matches!(
+                Where::is_in("id", value.clone()),
+                Err(OrmoluError::InvalidList(found)) if found == value
+            )
+        };
+
+        assert!(refused(SqlValue::Array(vec![
+            SqlValue::Integer(1),
+            SqlValue::Text("2".into())
+        ])));
+        assert!(refused(SqlValue::Array(vec![
+            SqlValue::Integer(1),
+            SqlValue::Null
+        ])));
+        assert!(refused(SqlValue::Array(vec![SqlValue::Array(vec![
+            SqlValue::Integer(1)
+        ])])));
+        assert!(refused(SqlValue::Integer(1)));
+
+        assert!(Where::is_in("id", SqlValue::Array(Vec::new())).is_ok());
+        assert!(Where::is_in("id", SqlValue::Array(vec![SqlValue::BigInt(1)])).is_ok());
+    }
+
+    #[test]
+    fn membership_in_subqueries() {
+        let big_spenders = QuerySet::new(QueryState::<Order>::new_select())
+            .filter(|o| o.total_amount > 100.0)
+            .values(|o| o.customer_id);
+
+        let f = QuerySet::new(QueryState::<Customer>::new_select())
+            .filter(|c| c.id.is_in(&big_spenders) && c.first_name == "Ann");
+
+        // Can still be moved into a spawned task
+        fn assert_send<T: Send>(_: &T) {}
+        assert_send(&f);
+
+        let (sql, values) = f.to_sql();
+        assert!(sql.ends_with(
+            "WHERE \"id\" IN (SELECT \"public\".\"order\".\"customer_id\" FROM \"public\".\"order\" WHERE \"public\".\"order\".\"total_amount\" > $1) AND \"first_name\" = $2;"
+        ));
+        assert_eq!(
+            values,
+            vec![SqlValue::Double(100.0), SqlValue::Text("Ann".into())]
+        );
+
+        // NULL phone numbers are left out of the subquery and kept by the NOT IN
+        let f = QuerySet::new(QueryState::<Customer>::new_select()).filter(|c| {
+            !c.phone_number.is_in(
+                QuerySet::new(QueryState::<Customer>::new_select())
+                    .filter(|other| other.last_name == "Smith")
+                    .values(|other| other.phone_number),
+            )
+        });
+        assert!(f.to_sql().0.ends_with(
+            "WHERE (\"phone_number\" NOT IN (SELECT \"public\".\"customer\".\"phone_number\" FROM \"public\".\"customer\" WHERE \"public\".\"customer\".\"last_name\" = $1 AND \"public\".\"customer\".\"phone_number\" IS NOT NULL) OR \"phone_number\" IS NULL);"
+        ));
+    }
+
+    #[test]
+    #[allow(clippy::neg_cmp_op_on_partial_ord)] // the negation is what is being tested
+    fn column_to_column_comparisons() {
+        let f = QuerySet::new(QueryState::<Order>::new_select())
+            .filter(|o| o.updated_at > o.created_at && o.status == "paid");
+
+        let (sql, values) = f.to_sql();
+        assert!(sql.ends_with("WHERE \"updated_at\" > \"created_at\" AND \"status\" = $1;"));
+        assert_eq!(values, vec![SqlValue::Text("paid".into())]);
+
+        let f = QuerySet::new(QueryState::<Order>::new_select())
+            .filter(|o| !(o.updated_at > o.created_at));
+        assert!(
+            f.to_sql()
+                .0
+                .ends_with("WHERE \"updated_at\" <= \"created_at\";")
+        );
+    }
+
+    #[test]
+    fn arithmetic_expressions() {
+        #[derive(Table)]
+        #[name = "public.product"]
+        pub struct Product {
+            id: PrimaryKey<Self, i32>,
+            stock_quantity: i32,
+            reorder_level: i32,
+            price: f64,
+            discount: f64,
+        }
+
+        let f = QuerySet::new(QueryState::<Product>::new_select()).filter(|p| {
+            p.stock_quantity * 2 < p.reorder_level
+                && (&p.price - &p.discount) * 1.2 >= 10.0
+                && &p.price / (&p.discount + 1.0) > &p.price - 3.0
+        });
+
+        let (sql, values) = f.to_sql();
+        assert!(sql.ends_with(
+            "WHERE \"stock_quantity\" * $1 < \"reorder_level\" AND (\"price\" - \"discount\") * $2 >= $3 AND \"price\" / (\"discount\" + $4) > \"price\" - $5;"
+        ));
+        assert_eq!(
+            values,
+            vec![
+                SqlValue::Integer(2),
+                SqlValue::Double(1.2),
+                SqlValue::Double(10.0),
+                SqlValue::Double(1.0),
+                SqlValue::Double(3.0)
+            ]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::nonminimal_bool, clippy::neg_cmp_op_on_partial_ord)] // the negations are spelled out on purpose
+    fn negated_ordering_flips_operator() {
+        let f = QuerySet::new(QueryState::<Order>::new_select())
+            .filter(|order| !(order.total_amount > 100.0) || !(order.id <= 3));
+
+        assert_eq!(
+            f.to_sql().0,
+            "SELECT \"id\", \"customer_id\", \"order_date\", \"total_amount\", \"status\", \"test\", \"name\", \"shipping_address\", \"created_at\", \"updated_at\" FROM \"public\".\"order\" WHERE \"total_amount\" <= $1 OR \"id\" > $2;"
+        );
+    }
+
+    fn normal_filter() {
+        let names = vec![
+            String::from("Alice"),
+            String::from("Bob"),
             String::from("Charlie"),
             String::from("Amanda"),
         ];
@@ -337,6 +1317,156 @@ mod filter_proxy_iter_dsl {
     }
 }
 
+#[cfg(test)]
+mod filter_tracing {
+    use crate::{query::*, *};
+    use std::collections::HashMap;
+
+    #[derive(Table)]
+    #[name = "public.flags"]
+    pub struct Flags {
+        a: bool,
+        b: bool,
+        c: bool,
+        d: bool,
+    }
+
+    /// Evaluates a traced condition where each column of `Flags` has a value.
+    fn eval(q: &Q, row: &HashMap<&str, bool>) -> bool {
+        match q {
+            Q::Where(w) => {
+                let (sql, values) = Q::from(w.clone()).to_sql();
+                let column = sql.split('"').nth(1).unwrap();
+                let matches = SqlValue::Bool(row[column]) == values[0];
+                if sql.contains("!=") {
+                    !matches
+                } else {
+                    matches
+                }
+            }
+            Q::And(conditions) => conditions.iter().all(|c| eval(c, row)),
+            Q::Or(conditions) => conditions.iter().any(|c| eval(c, row)),
+            Q::Not(inner) => !eval(inner, row),
+        }
+    }
+
+    /// Traces the predicate, checks the SQL and that the condition agrees with
+    /// the plain Rust expression for every combination of column values.
+    macro_rules! assert_traced {
+        (|$a:ident, $b:ident, $c:ident, $d:ident| $predicate:expr, $sql:expr) => {{
+            let condition = FieldsFilter::new::<Flags, _>(|f| {
+                let $a = || f.a == true;
+                let $b = || f.b == true;
+                let $c = || f.c == true;
+                let $d = || f.d == true;
+                $predicate
+            })
+            .condition;
+
+            assert_eq!(condition.to_sql().0, $sql);
+
+            for bits in 0..16u8 {
+                let row: HashMap<&str, bool> = ["a", "b", "c", "d"]
+                    .iter()
+                    .enumerate()
+                    .map(|(i, col)| (*col, bits & (1 << i) != 0))
+                    .collect();
+
+                let $a = || row["a"];
+                let $b = || row["b"];
+                let $c = || row["c"];
+                let $d = || row["d"];
+
+                assert_eq!(
+                    eval(&condition, &row),
+                    $predicate,
+                    "{} disagrees for {row:?}",
+                    $sql
+                );
+            }
+        }};
+    }
+
+    #[test]
+    fn single_conditions() {
+        assert_traced!(|a, b, c, d| a(), "\"a\" = $1");
+        assert_traced!(|a, b, c, d| !a(), "\"a\" != $1");
+    }
+
+    #[test]
+    fn flat_and_or() {
+        assert_traced!(
+            |a, b, c, d| a() && b() && c(),
+            "\"a\" = $1 AND \"b\" = $2 AND \"c\" = $3"
+        );
+        assert_traced!(
+            |a, b, c, d| a() || b() || c(),
+            "\"a\" = $1 OR \"b\" = $2 OR \"c\" = $3"
+        );
+    }
+
+    #[test]
+    fn nested_groups() {
+        assert_traced!(
+            |a, b, c, d| a() && (b() || c()),
+            "\"a\" = $1 AND (\"b\" = $2 OR \"c\" = $3)"
+        );
+        assert_traced!(
+            |a, b, c, d| a() || (b() && c()),
+            "\"a\" = $1 OR (\"b\" = $2 AND \"c\" = $3)"
+        );
+        assert_traced!(
+            |a, b, c, d| (a() && b()) || (c() && d()),
+            "(\"a\" = $1 AND \"b\" = $2) OR (\"c\" = $3 AND \"d\" = $4)"
+        );
+        assert_traced!(
+            |a, b, c, d| (a() || b()) && (c() || d()),
+            "(\"a\" = $1 OR \"b\" = $2) AND (\"c\" = $3 OR \"d\" = $4)"
+        );
+        assert_traced!(
+            |a, b, c, d| (a() || b()) && c(),
+            "(\"a\" = $1 OR \"b\" = $2) AND \"c\" = $3"
+        );
+        assert_traced!(
+            |a, b, c, d| a() && (b() || (c() && d())),
+            "\"a\" = $1 AND (\"b\" = $2 OR (\"c\" = $3 AND \"d\" = $4))"
+        );
+    }
+
+    #[test]
+    #[allow(clippy::nonminimal_bool)] // the predicates are spelled out on purpose
+    fn negated_groups() {
+        assert_traced!(|a, b, c, d| !(a() || b()), "\"a\" != $1 AND \"b\" != $2");
+        assert_traced!(|a, b, c, d| !(a() && b()), "\"a\" != $1 OR \"b\" != $2");
+        assert_traced!(|a, b, c, d| !a() || b(), "\"a\" != $1 OR \"b\" = $2");
+        assert_traced!(
+            |a, b, c, d| (!a() && b()) || c(),
+            "(\"a\" != $1 AND \"b\" = $2) OR \"c\" = $3"
+        );
+        assert_traced!(
+            |a, b, c, d| a() && !(b() && !c()),
+            "\"a\" = $1 AND (\"b\" != $2 OR \"c\" = $3)"
+        );
+        assert_traced!(
+            |a, b, c, d| !(a() || (b() && !(c() || d()))),
+            "\"a\" != $1 AND (\"b\" != $2 OR \"c\" = $3 OR \"d\" = $4)"
+        );
+    }
+
+    #[test]
+    fn formulas_without_and_or_shape() {
+        // exclusive or has no simpler form than spelling out both cases
+        assert_traced!(
+            |a, b, c, d| a() ^ b(),
+            "(\"a\" = $1 AND \"b\" != $2) OR (\"a\" != $3 AND \"b\" = $4)"
+        );
+        assert_traced!(
+            |a, b, c, d| if a() { b() } else { c() },
+            "(\"a\" = $1 AND \"b\" = $2) OR (\"a\" != $3 AND \"c\" = $4)"
+        );
+    }
+}
+
 #[cfg(test)]
 mod iter_eval {
     use std::marker::PhantomData;
@@ -445,53 +1575,172 @@ mod iter_eval {
 
         // for order in filtered_orders {}
 
-        // TODO: id Field needs to be renamed to customer_id when made into request
-        assert_eq!(filtered_orders.to_string(), "SELECT id, customer_id, order_date, total_amount, status, name, shipping_address, created_at, updated_at FROM order WHERE name NOT LIKE %test% AND customer_id != 2 AND name LIKE %john%;".to_string())
+        // the id field is the order_id column
+        assert_eq!(filtered_orders.to_string(), "SELECT \"order_id\", \"customer_id\", \"order_date\", \"total_amount\", \"status\", \"name\", \"shipping_address\", \"created_at\", \"updated_at\" FROM \"public\".\"order\" WHERE \"name\" NOT LIKE $1 AND \"order_id\" != $2 AND \"name\" LIKE $3;".to_string())
     }
 }
 
 #[cfg(test)]
 mod relations {
+    use super::{Customer, Order, customers, orders};
     use crate::{query::*, *};
     use futures::StreamExt;
 
     #[derive(Table)]
-    #[schema = "public"]
-    #[object = "customer"]
-    pub struct Customer {
-        id: PrimaryKey<Self, i32>,
-        first_name: String,
-        last_name: String,
-        email: Unique<String>,
-        phone_number: Option<Unique<String>>,
-        created_at: chrono::NaiveDateTime,
-        updated_at: chrono::NaiveDateTime,
+    #[name = "public.shipment"]
+    pub struct Shipment {
+        id: Identity<PrimaryKey<Self, i64>>,
+        order_id: Option<ForeignKey<Order, 1, i32>>,
+        weight: f32,
     }
 
     #[derive(Table)]
-    #[schema = "public"]
-    #[object = "order"]
-    pub struct Order {
-        id: PrimaryKey<Self, i32>,
-        customer_id: ForeignKey<Customer, 1, i32>,
-        order_date: chrono::NaiveDateTime,
-        total_amount: f64,
-        status: String,
+    #[name = "public.invoice"]
+    pub struct Invoice {
+        id: Identity<PrimaryKey<Self, i32>>,
+        billing_customer_id: ForeignKey<Customer, 1, i32>,
+        shipping_customer_id: ForeignKey<Customer, 1, i32>,
+        total: f64,
+    }
+
+    #[derive(Table)]
+    #[name = "public.employee"]
+    pub struct Employee {
+        id: Identity<PrimaryKey<Self, i32>>,
+        manager_id: Option<ForeignKey<Employee, 1, i32>>,
         name: String,
-        shipping_address: String,
-        test: bool,
-        created_at: chrono::NaiveDateTime,
-        updated_at: chrono::NaiveDateTime,
     }
+
+    fn where_clause(sql: String) -> String {
+        sql.split_once(" WHERE ")
+            .expect("no WHERE clause")
+            .1
+            .to_string()
+    }
+
+    #[test]
+    fn filter_on_the_referenced_row() {
+        let (sql, values) = orders()
+            .filter(|o| o.customer_id.related().email == "ann@example.com" || o.status == "paid")
+            .to_sql();
+
+        assert_eq!(
+            where_clause(sql),
+            "\"customer_id\" IN (SELECT \"public\".\"customer\".\"id\" FROM \"public\".\"customer\" WHERE \"public\".\"customer\".\"email\" = $1) OR \"status\" = $2;"
+        );
+        assert_eq!(
+            values,
+            vec![
+                SqlValue::Text("ann@example.com".into()),
+                SqlValue::Text("paid".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn follow_nullable_keys_across_tables() {
+        let q = QuerySet::new(QueryState::<Shipment>::new_select()).filter(|s| {
+            !s.order_id
+                .related()
+                .customer_id
+                .related()
+                .last_name
+                .starts_with("Sm")
+        });
+
+        assert_eq!(
+            where_clause(q.to_sql().0),
+            "(\"order_id\" NOT IN (SELECT \"public\".\"order\".\"id\" FROM \"public\".\"order\" WHERE \"public\".\"order\".\"customer_id\" IN (SELECT \"public\".\"customer\".\"id\" FROM \"public\".\"customer\" WHERE \"public\".\"customer\".\"last_name\" LIKE $1)) OR \"order_id\" IS NULL);"
+        );
+    }
+
+    #[test]
+    fn filter_on_any_referencing_row() {
+        let (sql, values) = customers()
+            .filter(|c| {
+                c.id.any::<Order>(|o| o.total_amount > 100.0 && o.status == "paid")
+            })
+            .to_sql();
+
+        assert_eq!(
+            where_clause(sql),
+            "\"id\" IN (SELECT \"public\".\"order\".\"customer_id\" FROM \"public\".\"order\" WHERE \"public\".\"order\".\"total_amount\" > $1 AND \"public\".\"order\".\"status\" = $2 AND \"public\".\"order\".\"customer_id\" IS NOT NULL);"
+        );
+        assert_eq!(
+            values,
+            vec![SqlValue::Double(100.0), SqlValue::Text("paid".into())]
+        );
+    }
+
+    #[test]
+    fn filter_on_no_referencing_row() {
+        let q = orders().filter(|o| !o.id.any::<Shipment>(|_| true));
+
+        assert_eq!(
+            where_clause(q.to_sql().0),
+            "\"id\" NOT IN (SELECT \"public\".\"shipment\".\"order_id\" FROM \"public\".\"shipment\" WHERE \"public\".\"shipment\".\"order_id\" IS NOT NULL);"
+        );
+    }
+
+    #[test]
+    fn filter_on_any_row_referencing_via_a_picked_key() {
+        let (sql, values) = customers()
+            .filter(|c| {
+                c.id.any_via::<Invoice, _>(|i| i.shipping_customer_id, |i| i.total > 100.0)
+            })
+            .to_sql();
+
+        assert_eq!(
+            where_clause(sql),
+            "\"id\" IN (SELECT \"public\".\"invoice\".\"shipping_customer_id\" FROM \"public\".\"invoice\" WHERE \"public\".\"invoice\".\"total\" > $1 AND \"public\".\"invoice\".\"shipping_customer_id\" IS NOT NULL);"
+        );
+        assert_eq!(values, vec![SqlValue::Double(100.0)]);
+
+        // managers of someone called Ann, the subquery reads the inner employee
+        let q = QuerySet::new(QueryState::<Employee>::new_select()).filter(|e| {
+            e.id.any_via::<Employee, _>(|r| r.manager_id, |r| r.name == "Ann")
+        });
+        assert_eq!(
+            where_clause(q.to_sql().0),
+            "\"id\" IN (SELECT \"public\".\"employee\".\"manager_id\" FROM \"public\".\"employee\" WHERE \"public\".\"employee\".\"name\" = $1 AND \"public\".\"employee\".\"manager_id\" IS NOT NULL);"
+        );
+    }
+
     #[test]
     fn subtype() {
         // #[derive(Table)]
         pub struct FrontendOrder {}
     }
 
+    #[tokio::test]
+    async fn foreign_key_fetches_the_referenced_row() {
+        let pool = super::test_pool().await;
+
+        let key: ForeignKey<Customer, 1, i32> = 1.into();
+        let customer = key.get_entity(&pool).await.unwrap().expect("no customer 1");
+        assert_eq!(**customer.id, 1);
+
+        let missing: ForeignKey<Customer, 1, i32> = (-1).into();
+        assert!(missing.get_entity(&pool).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn foreign_key_past_the_last_column() {
+        // never connects, the ordinal is checked first
+        let pool = sqlx::PgPool::connect_lazy("postgres://localhost/ormolu").unwrap();
+
+        let key: ForeignKey<Customer, 99, i32> = 1.into();
+        assert!(matches!(
+            key.get_entity(&pool).await,
+            Err(OrmoluError::NoSuchColumn { ordinal: 99, .. })
+        ));
+    }
+
     #[tokio::test]
     async fn one_to_many() {
-        let Ok(Some(customer)) = Customer::get_by_id(1.into()).await else {
+        let pool = super::test_pool().await;
+        let Ok(Some(customer)) = Customer::get_by_id(&pool, PrimaryKey::from(1).into()).await
+        else {
             panic!("failed to get cust")
         };
 
@@ -542,6 +1791,7 @@ mod relations {
 mod database {
     use std::marker::PhantomData;
 
+    use super::customers;
     use crate::{query::*, *};
     use ormolu_interfaces::GetConnectionPool;
     use sqlx::FromRow;
@@ -555,16 +1805,57 @@ mod database {
         modified: chrono::NaiveDateTime,
     }
 
+    #[tokio::test]
+    async fn stream_rows_in_chunks() {
+        use futures::TryStreamExt;
+
+        let pool = super::test_pool().await;
+        let fetched: Vec<_> = customers()
+            .order_by_asc(|c| c.id)
+            .all(&pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+
+        // a chunk of one row makes every row a separate FETCH
+        let streamed: Vec<_> = customers()
+            .order_by_asc(|c| c.id)
+            .chunk_size(1)
+            .map_ok(|c| c.id)
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(streamed, fetched);
+
+        // in a transaction the cursor is declared in a savepoint
+        let mut tx = pool.begin().await.unwrap();
+        let in_tx: Vec<_> = customers()
+            .order_by_asc(|c| c.id)
+            .chunk_size(2)
+            .stream(&mut tx)
+            .map_ok(|c| c.id)
+            .try_collect()
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        assert_eq!(in_tx, fetched);
+    }
+
     #[tokio::test]
     async fn get_connection_pool() {
-        let pool = ResourceType::get_connection_pool().await;
+        let pool = ResourceType::get_connection_pool().await.unwrap();
     }
 
     #[tokio::test]
     async fn fetch_via_model() {
         let pk = PrimaryKey::from(1);
 
-        if let Ok(Some(resource_type)) = ResourceType::get_by_id(pk).await {
+        let pool = super::test_pool().await;
+        if let Ok(Some(resource_type)) = ResourceType::get_by_id(&pool, pk).await {
             assert_eq!(resource_type.id, pk);
             assert_eq!(resource_type.name, String::from("test"))
         } else {
@@ -643,38 +1934,138 @@ mod get_pool {
         //     .filter(|order| !order.name.contains("test"))
         //     .filter(|order| order.id != 2 && order.name.contains("john"));
     }
-}
-
-#[cfg(test)]
-mod select {
-    use crate::{query::*, *};
 
     #[derive(Table)]
-    #[schema = "public"]
-    #[object = "customer"]
-    pub struct Customer {
+    #[gild(database = "reports")]
+    #[name = "public.customer"]
+    pub struct ReportCustomer {
         id: PrimaryKey<Self, i32>,
-        first_name: String,
-        last_name: String,
         email: Unique<String>,
-        phone_number: Option<Unique<String>>,
-        created_at: chrono::NaiveDateTime,
-        updated_at: chrono::NaiveDateTime,
     }
 
     #[derive(Table)]
-    #[schema = "public"]
-    #[object = "order"]
-    pub struct Order {
+    #[gild(pool = custom_pool)]
+    #[name = "public.customer"]
+    pub struct PooledCustomer {
         id: PrimaryKey<Self, i32>,
-        customer_id: ForeignKey<Customer, 1, i32>,
-        order_date: chrono::NaiveDateTime,
-        total_amount: f64,
-        status: String,
-        name: String,
-        shipping_address: String,
-        created_at: chrono::NaiveDateTime,
-        updated_at: chrono::NaiveDateTime,
+        email: Unique<String>,
+    }
+
+    async fn custom_pool() -> Result<sqlx::PgPool, OrmoluError> {
+        Ok(PgPoolOptions::new()
+            .max_connections(1)
+            .connect_lazy("postgres://localhost/custom")?)
+    }
+
+    #[tokio::test]
+    async fn configured_pool() {
+        let config = PoolConfig {
+            url: Some("postgres://localhost/configured".to_string()),
+            max_connections: 3,
+            ..Default::default()
+        };
+        configure_pool("configured", config.clone()).unwrap();
+
+        let pool = database_pool("configured").unwrap();
+        assert_eq!(pool.options().get_max_connections(), 3);
+
+        // Too late to change once queries are using the pool
+        assert!(matches!(
+            configure_pool("configured", config),
+            Err(OrmoluError::PoolInUse { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn registered_pool() {
+        let pool = PgPoolOptions::new()
+            .max_connections(2)
+            .connect_lazy("postgres://localhost/registered")
+            .unwrap();
+        register_pool("registered", pool).unwrap();
+
+        let pool = database_pool("registered").unwrap();
+        assert_eq!(pool.options().get_max_connections(), 2);
+    }
+
+    #[test]
+    fn missing_database_url() {
+        match database_pool("nowhere") {
+            Err(OrmoluError::MissingDatabaseUrl { database, variable }) => {
+                assert_eq!(database, "nowhere");
+                assert_eq!(variable, "NOWHERE_DATABASE_URL");
+            }
+            _ => panic!("expected a missing database url"),
+        }
+    }
+
+    #[tokio::test]
+    async fn named_database() {
+        assert_eq!(ReportCustomer::DATABASE, "reports");
+        assert_eq!(PooledCustomer::DATABASE, DEFAULT_DATABASE);
+
+        let pool = PgPoolOptions::new()
+            .max_connections(4)
+            .connect_lazy("postgres://localhost/reports")
+            .unwrap();
+        register_pool("reports", pool).unwrap();
+
+        let pool = ReportCustomer::get_connection_pool().await.unwrap();
+        assert_eq!(pool.options().get_max_connections(), 4);
+    }
+
+    #[tokio::test]
+    async fn pool_attribute() {
+        let pool = PooledCustomer::get_connection_pool().await.unwrap();
+        assert_eq!(pool.options().get_max_connections(), 1);
+    }
+}
+
+#[cfg(test)]
+mod select {
+    use super::{Customer, customers};
+    use crate::{query::*, *};
+
+    #[test]
+    fn projection_lists_picked_columns_in_declaration_order() {
+        let q: QuerySet<Customer, (String, Unique<String>)> =
+            customers().filter(|c| c.last_name == "Smith").select(|s| {
+                s.email = true;
+                s.first_name = true;
+            });
+
+        assert_eq!(
+            q.to_string(),
+            "SELECT \"first_name\", \"email\" FROM \"public\".\"customer\" WHERE \"last_name\" = $1;"
+        );
+    }
+
+    #[test]
+    fn projection_decodes_into_partial_struct() {
+        #[derive(sqlx::FromRow)]
+        #[allow(dead_code)]
+        struct CustomerContact {
+            email: String,
+            phone_number: Option<String>,
+        }
+
+        let q = customers()
+            .order_by_asc(|c| c.email)
+            .select::<CustomerContact>(|s| {
+                s.email = true;
+                s.phone_number = true;
+            });
+
+        assert_eq!(
+            q.to_string(),
+            "SELECT \"email\", \"phone_number\" FROM \"public\".\"customer\" ORDER BY \"email\" ASC;"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "no field of public.customer is selected")]
+    fn empty_projection_is_refused() {
+        customers().select::<()>(|_| {});
     }
 
     // TODO: each custom select specific fields from x
@@ -766,6 +2157,428 @@ mod select {
     }
 }
 
+/// `query!` renders its statement once for every instance of a generic
+/// function, so the table cannot be a type parameter:
+/// ```compile_fail,E0401
+/// use ormolu::{query::*, *};
+///
+/// fn first_rows<T: Table>() -> StaticQuery<T> {
+///     query!(T.limit(10))
+/// }
+/// ```
+#[cfg(doctest)]
+pub struct QueryTableIsNotGeneric;
+
+#[cfg(test)]
+mod static_query {
+    use super::{Customer, Order};
+    use crate::{query::*, *};
+
+    fn open_orders(status: &str, min_total: f64) -> StaticQuery<Order> {
+        query!(
+            Order
+                .filter(|o| o.status == status && o.total_amount * 1.1 > min_total)
+                .filter(|o| !o.note.is_null()
+                    || o.customer_id.related().email.ends_with("@example.com"))
+                .order_by_desc(|o| o.created_at)
+        )
+    }
+
+    #[test]
+    fn renders_like_a_query_set() {
+        let (status, min_total) = ("paid", 100.0);
+        let (sql, values) = QuerySet::new(QueryState::<Order>::new_select())
+            .filter(|o| o.status == status && o.total_amount * 1.1 > min_total)
+            .filter(|o| {
+                !o.note.is_null() || o.customer_id.related().email.ends_with("@example.com")
+            })
+            .order_by_desc(|o| o.created_at)
+            .to_sql();
+
+        let q = open_orders(status, min_total);
+        assert_eq!(q.sql(), sql);
+        assert_eq!(q.values(), values);
+    }
+
+    #[test]
+    fn statement_is_rendered_once() {
+        let paid = open_orders("paid", 100.0);
+        let shipped = open_orders("shipped", 20.0);
+
+        assert!(std::ptr::eq(paid.sql(), shipped.sql()));
+        assert_eq!(
+            shipped.values(),
+            [
+                SqlValue::Text("shipped".into()),
+                SqlValue::Double(1.1),
+                SqlValue::Double(20.0),
+                SqlValue::Text("%@example.com".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn values_in_nested_closures_and_projections() {
+        let min_total = 250.0;
+        let q: StaticQuery<Customer, (Unique<String>,)> = query!(
+            Customer
+                .filter(|c| c
+                    .id
+                    .any::<Order>(|o| o.total_amount >= min_total && o.status == "paid"))
+                .select(|s| s.email = true)
+        );
+
+        assert_eq!(
+            q.sql(),
+            "SELECT \"email\" FROM \"public\".\"customer\" WHERE \"id\" IN (SELECT \"public\".\"order\".\"customer_id\" FROM \"public\".\"order\" WHERE \"public\".\"order\".\"total_amount\" >= $1 AND \"public\".\"order\".\"status\" = $2 AND \"public\".\"order\".\"customer_id\" IS NOT NULL);"
+        );
+        assert_eq!(
+            q.values(),
+            [SqlValue::Double(250.0), SqlValue::Text("paid".into())]
+        );
+    }
+}
+
+#[cfg(test)]
+mod terminal_methods {
+    use super::{Customer, customers};
+    use crate::{query::*, *};
+
+    fn named_smith() -> QueryState<Customer> {
+        let mut state = QueryState::new_select();
+        state.and_where(Where::eq("first_name", "Smith"));
+        state.order_by = Some(OrderBy::new(OrderByTerm {
+            column: "email".into(),
+            direction: SortDirection::Asc,
+            nulls: None,
+        }));
+        state
+    }
+
+    #[test]
+    fn count_replaces_the_columns() {
+        let (sql, values) = named_smith().into_count_sql();
+
+        assert_eq!(
+            sql,
+            "SELECT count(*) FROM \"public\".\"customer\" WHERE \"first_name\" = $1;"
+        );
+        assert_eq!(values, vec![SqlValue::Text("Smith".into())]);
+    }
+
+    #[test]
+    fn count_of_a_limited_query_counts_its_rows() {
+        let mut state = named_smith();
+        state.limit = Some(10);
+
+        assert_eq!(
+            state.into_count_sql().0,
+            "SELECT count(*) FROM (SELECT \"id\", \"first_name\", \"last_name\", \"email\", \"phone_number\", \"created_at\", \"updated_at\" FROM \"public\".\"customer\" WHERE \"first_name\" = $1 ORDER BY \"email\" ASC LIMIT 10) AS \"rows\";"
+        );
+    }
+
+    #[test]
+    fn count_of_groups() {
+        let mut state = named_smith();
+        state.group_by = Some(GroupBy::new(vec!["email".into()]));
+
+        assert_eq!(
+            state.into_count_sql().0,
+            "SELECT count(*) FROM (SELECT \"id\", \"first_name\", \"last_name\", \"email\", \"phone_number\", \"created_at\", \"updated_at\" FROM \"public\".\"customer\" WHERE \"first_name\" = $1 GROUP BY \"email\") AS \"rows\";"
+        );
+    }
+
+    #[test]
+    fn exists_wraps_the_query() {
+        assert_eq!(
+            named_smith().into_exists_sql().0,
+            "SELECT EXISTS (SELECT \"id\", \"first_name\", \"last_name\", \"email\", \"phone_number\", \"created_at\", \"updated_at\" FROM \"public\".\"customer\" WHERE \"first_name\" = $1);"
+        );
+    }
+
+    #[test]
+    fn slicing() {
+        let q = customers().order_by_asc(|c| c.id).offset(40).limit(20);
+
+        assert_eq!(
+            q.to_sql().0,
+            "SELECT \"id\", \"first_name\", \"last_name\", \"email\", \"phone_number\", \"created_at\", \"updated_at\" FROM \"public\".\"customer\" ORDER BY \"id\" ASC LIMIT 20 OFFSET 40;"
+        );
+    }
+
+    /// The futures stay `Send` when the executor is only borrowed for a
+    /// lifetime the caller picks, as in a boxed closure
+    #[test]
+    fn futures_are_send_with_borrowed_executors() {
+        fn run<F>(_: F)
+        where
+            F: for<'t> FnOnce(
+                &'t mut sqlx::PgConnection,
+            )
+                -> futures::future::BoxFuture<'t, Result<(), OrmoluError>>,
+        {
+        }
+
+        run(|conn| {
+            Box::pin(async move {
+                customers().all(&mut *conn).await?;
+                customers().one(&mut *conn).await?;
+                customers().get(&mut *conn, PrimaryKey::from(1)).await?;
+                customers().count(&mut *conn).await?;
+                customers().exists(&mut *conn).await?;
+                customers()
+                    .filter(|c| c.id == 1)
+                    .update(&mut *conn, |c| c.first_name.set("Jo".to_string()))
+                    .await?;
+                customers().filter(|c| c.id == 1).delete(&mut *conn).await?;
+                query!(Customer.filter(|c| c.id == 1))
+                    .all(&mut *conn)
+                    .await?;
+
+                Customer::get_by_email(&mut *conn, "jo@example.com").await?;
+                if let Some(customer) = customers().first(&mut *conn).await? {
+                    customer.insert(&mut *conn).await?;
+                    customer.update(&mut *conn).await?;
+                    customer.delete(&mut *conn).await?;
+                }
+                Ok(())
+            })
+        });
+    }
+
+    #[tokio::test]
+    async fn fetch_single_rows() {
+        let pool = super::test_pool().await;
+
+        let all = customers().all(&pool).await.unwrap();
+        assert_eq!(customers().count(&pool).await.unwrap(), all.len() as i64);
+        assert_eq!(customers().exists(&pool).await.unwrap(), !all.is_empty());
+
+        let first = customers().order_by_asc(|c| c.id).first(&pool).await;
+        let Some(first) = first.unwrap() else { return };
+
+        let id = **first.id;
+        let one = customers().filter(|c| c.id == id).one(&pool).await.unwrap();
+        assert_eq!(*one.email, *first.email);
+
+        let by_key = customers().get(&pool, PrimaryKey::from(id)).await.unwrap();
+        assert_eq!(by_key.map(|c| **c.id), Some(id));
+
+        assert!(matches!(
+            customers().filter(|c| c.id < 0).one(&pool).await,
+            Err(OrmoluError::NotFound { .. })
+        ));
+    }
+}
+
+#[cfg(test)]
+mod transactions {
+    use super::{Customer, Order};
+    use crate::{query::*, *};
+
+    fn customers(email: &'static str) -> QuerySet<Customer> {
+        QuerySet::<Customer>::new(QueryState::new_select()).filter(move |c| c.email == email)
+    }
+
+    fn orders(name: &'static str) -> QuerySet<Order> {
+        QuerySet::<Order>::new(QueryState::new_select()).filter(move |o| o.name == name)
+    }
+
+    fn order(customer_id: Option<i32>, name: &str) -> Order {
+        Order {
+            id: PrimaryKey::from(0).into(),
+            customer_id: customer_id.map(ForeignKey::from),
+            name: name.to_string(),
+            status: "new".to_string(),
+            total_amount: 0.0,
+            quantity: None,
+            note: None,
+            test: false,
+            created_at: chrono::NaiveDateTime::default(),
+        }
+    }
+
+    /// Only runs in transactions that are rolled back, other tests count the
+    /// customers.
+    async fn place_order(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        email: &str,
+        name: &str,
+    ) -> Result<Order, OrmoluError> {
+        let customer = Customer {
+            id: PrimaryKey::from(0).into(),
+            first_name: "Ann".to_string(),
+            last_name: "Lee".to_string(),
+            email: Unique::from(email.to_string()),
+            phone_number: None,
+            created_at: chrono::NaiveDateTime::default(),
+            updated_at: chrono::NaiveDateTime::default(),
+        }
+        .insert(&mut *tx)
+        .await?;
+
+        order(Some(**customer.id), name).insert(&mut *tx).await
+    }
+
+    #[tokio::test]
+    async fn commit_on_ok() {
+        let pool = super::test_pool().await;
+        orders("commit").delete(&pool).await.unwrap();
+
+        let (first, second) = transaction(&pool, |tx| {
+            Box::pin(async move {
+                let first = order(None, "commit").insert(&mut *tx).await?;
+                let second = order(None, "commit").insert(&mut *tx).await?;
+                Ok::<_, OrmoluError>((first, second))
+            })
+        })
+        .await
+        .unwrap();
+
+        let stored: Vec<_> = orders("commit")
+            .order_by_asc(|o| o.id)
+            .all(&pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|o| **o.id)
+            .collect();
+        assert_eq!(stored, [**first.id, **second.id]);
+
+        orders("commit").delete(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn insert_many_keeps_the_order_of_the_rows() {
+        let pool = super::test_pool().await;
+        let mut tx = pool.begin().await.unwrap();
+
+        let rows = [
+            order(None, "third"),
+            order(None, "first"),
+            order(None, "second"),
+        ];
+        let inserted = Order::insert_many(&mut *tx, &rows).await.unwrap();
+
+        let names: Vec<_> = inserted.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(names, ["third", "first", "second"]);
+        assert!(inserted.windows(2).all(|w| **w[0].id < **w[1].id));
+    }
+
+    #[tokio::test]
+    async fn rollback_on_err() {
+        let pool = super::test_pool().await;
+
+        let result: Result<(), OrmoluError> = transaction(&pool, |tx| {
+            Box::pin(async move {
+                place_order(tx, "rollback@ormolu.test", "rollback").await?;
+                Err(OrmoluError::Other("out of stock".to_string()))
+            })
+        })
+        .await;
+
+        assert!(matches!(result, Err(OrmoluError::Other(_))));
+        assert!(!orders("rollback").exists(&pool).await.unwrap());
+        assert!(
+            !customers("rollback@ormolu.test")
+                .exists(&pool)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn rollback_on_panic() {
+        let pool = super::test_pool().await;
+
+        let task = tokio::spawn({
+            let pool = pool.clone();
+            async move {
+                transaction(&pool, |tx| {
+                    Box::pin(async move {
+                        place_order(tx, "panic@ormolu.test", "panic").await?;
+                        panic!("payment provider unreachable");
+                        #[allow(unreachable_code)]
+                        Ok::<_, OrmoluError>(())
+                    })
+                })
+                .await
+            }
+        });
+
+        assert!(task.await.unwrap_err().is_panic());
+        assert!(!orders("panic").exists(&pool).await.unwrap());
+        assert!(!customers("panic@ormolu.test").exists(&pool).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn stream_twice_in_one_transaction() {
+        use futures::{StreamExt, TryStreamExt};
+
+        let pool = super::test_pool().await;
+        let all_orders = || QuerySet::<Order>::new(QueryState::new_select()).order_by_asc(|o| o.id);
+
+        let (stored, first, second) = transaction(&pool, |tx| {
+            Box::pin(async move {
+                let stored = all_orders().all(&mut *tx).await?.len();
+
+                // read to the end, then dropped before the last row
+                let first: Vec<_> = all_orders()
+                    .chunk_size(1)
+                    .stream(&mut *tx)
+                    .try_collect()
+                    .await?;
+                all_orders().stream(&mut *tx).next().await.transpose()?;
+                let second: Vec<_> = all_orders()
+                    .chunk_size(1)
+                    .stream(&mut *tx)
+                    .try_collect()
+                    .await?;
+
+                Ok::<_, OrmoluError>((stored, first.len(), second.len()))
+            })
+        })
+        .await
+        .unwrap();
+
+        assert_eq!((first, second), (stored, stored));
+    }
+
+    #[tokio::test]
+    async fn nested_transactions_are_savepoints() {
+        let pool = super::test_pool().await;
+        orders("outer").delete(&pool).await.unwrap();
+
+        transaction(&pool, |tx| {
+            Box::pin(async move {
+                order(None, "outer").insert(&mut *tx).await?;
+
+                let declined: Result<(), OrmoluError> = transaction(&mut *tx, |tx| {
+                    Box::pin(async move {
+                        place_order(tx, "inner@ormolu.test", "inner").await?;
+                        Err(OrmoluError::Other("declined".to_string()))
+                    })
+                })
+                .await;
+                assert!(declined.is_err());
+
+                // The savepoint only undid what was written in it
+                assert!(orders("outer").exists(&mut *tx).await?);
+                assert!(!orders("inner").exists(&mut *tx).await?);
+                Ok::<_, OrmoluError>(())
+            })
+        })
+        .await
+        .unwrap();
+
+        assert!(orders("outer").exists(&pool).await.unwrap());
+        assert!(!orders("inner").exists(&pool).await.unwrap());
+        assert!(!customers("inner@ormolu.test").exists(&pool).await.unwrap());
+
+        orders("outer").delete(&pool).await.unwrap();
+    }
+}
+
 #[cfg(test)]
 mod convert_schema_to_source {
 
@@ -1,6 +1,74 @@
+use crate::{SqlValue, ToSqlValue};
+use sqlx::{Database, Decode, prelude::Type};
+
 /// A wrapper type representing a PostgreSQL char with a fixed length.
 ///
 /// This struct is a transparent wrapper around a byte array of a specified constant size `N`,
 /// designed to represent PostgreSQL character types.
 #[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Char<const N: usize>([u8; N]);
+
+impl<const N: usize> Char<N> {
+    /// Try to create a Char from a `&str`, padded with spaces like Postgres
+    /// does. Will error if too long.
+    pub fn new(s: &str) -> Result<Self, &'static str> {
+        let bytes = s.as_bytes();
+        if bytes.len() > N {
+            return Err("String too long");
+        }
+
+        let mut inner = [b' '; N];
+        inner[..bytes.len()].copy_from_slice(bytes);
+        Ok(Self(inner))
+    }
+
+    /// Get the inner string, including the padding.
+    pub fn as_str(&self) -> &str {
+        str::from_utf8(&self.0).unwrap()
+    }
+}
+
+impl<const N: usize> std::fmt::Display for Char<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<const N: usize> TryFrom<&str> for Char<N> {
+    type Error = &'static str;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl<const N: usize> ToSqlValue for Char<N> {
+    fn to_sql_value(&self) -> SqlValue {
+        SqlValue::Text(self.as_str().to_string())
+    }
+}
+
+impl<DB: Database, const N: usize> Type<DB> for Char<N>
+where
+    String: Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <String as Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <String as Type<DB>>::compatible(ty)
+    }
+}
+
+impl<'r, DB: Database, const N: usize> Decode<'r, DB> for Char<N>
+where
+    &'r str: Decode<'r, DB>,
+{
+    fn decode(
+        value: <DB as sqlx::database::HasValueRef<'r>>::ValueRef,
+    ) -> Result<Self, sqlx::error::BoxDynError> {
+        Ok(Self::new(<&str as Decode<DB>>::decode(value)?)?)
+    }
+}
@@ -1,3 +1,6 @@
+use crate::{SqlValue, ToSqlValue};
+use sqlx::{Database, Decode, prelude::Type};
+
 #[repr(transparent)]
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub struct VarChar<const LENGTH: usize> {
@@ -57,3 +60,33 @@ impl<const LENGTH: usize> TryFrom<String> for VarChar<LENGTH> {
         Self::new(&value)
     }
 }
+
+impl<const LENGTH: usize> ToSqlValue for VarChar<LENGTH> {
+    fn to_sql_value(&self) -> SqlValue {
+        SqlValue::Text(self.as_str().to_string())
+    }
+}
+
+impl<DB: Database, const LENGTH: usize> Type<DB> for VarChar<LENGTH>
+where
+    String: Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <String as Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <String as Type<DB>>::compatible(ty)
+    }
+}
+
+impl<'r, DB: Database, const LENGTH: usize> Decode<'r, DB> for VarChar<LENGTH>
+where
+    &'r str: Decode<'r, DB>,
+{
+    fn decode(
+        value: <DB as sqlx::database::HasValueRef<'r>>::ValueRef,
+    ) -> Result<Self, sqlx::error::BoxDynError> {
+        Ok(Self::new(<&str as Decode<DB>>::decode(value)?)?)
+    }
+}
@@ -1,5 +1,8 @@
-use crate::{Key, Table};
-use sqlx::{Database, Decode, prelude::Type};
+use crate::{
+    Key, OrmoluError, PgAcquire, SqlValue, Table, ToSqlValue,
+    query::{QueryState, Where},
+};
+use sqlx::{Database, Decode, FromRow, postgres::PgRow, prelude::Type};
 use std::marker::PhantomData;
 
 #[repr(transparent)]
@@ -14,40 +17,73 @@ impl<E, const C: usize, T> AsRef<T> for ForeignKey<E, C, T> {
     }
 }
 
+impl<E, const C: usize, T> From<T> for ForeignKey<E, C, T> {
+    fn from(value: T) -> Self {
+        Self {
+            _entity: PhantomData,
+            value,
+        }
+    }
+}
+
+impl<E, const C: usize, T> ToSqlValue for ForeignKey<E, C, T>
+where
+    T: ToSqlValue,
+{
+    fn to_sql_value(&self) -> SqlValue {
+        self.value.to_sql_value()
+    }
+}
+
 impl<Entity, const ORDINAL: usize, T> Key<Entity, T> for ForeignKey<Entity, ORDINAL, T>
 where
-    Entity: Table,
+    Entity: Table + for<'r> FromRow<'r, PgRow> + Send + Unpin,
+    T: ToSqlValue,
 {
-    async fn get_entity(&self) -> Result<Option<Entity>, crate::OrmoluError> {
-        // TODO: OrmoluError this
-        let column_name = Entity::column(ORDINAL).expect("msg");
+    fn get_entity<'c>(
+        &self,
+        db: impl PgAcquire<'c>,
+    ) -> impl Future<Output = Result<Option<Entity>, OrmoluError>> + Send {
+        let statement = Entity::column(ORDINAL).map(|column| {
+            let mut q = QueryState::<Entity>::new_select();
+            q.and_where(Where::eq(column, self.to_sql_value()));
+            q.build()
+        });
+
+        async move {
+            let (sql, arguments) = statement.ok_or(OrmoluError::NoSuchColumn {
+                table: Entity::qualified_name(),
+                ordinal: ORDINAL,
+            })?;
+            let mut conn = db.acquire().await?;
 
-        // Entity::
-        // Entity::get_by_primary_key(key)
-        todo!()
+            Ok(sqlx::query_as_with::<_, Entity, _>(sql.as_str(), arguments)
+                .fetch_optional(&mut *conn)
+                .await?)
+        }
     }
 }
 
 impl<DB: Database, E, const ORDINAL: usize, T> Type<DB> for ForeignKey<E, ORDINAL, T>
 where
-    String: Type<DB>,
+    T: Type<DB>,
 {
     fn type_info() -> DB::TypeInfo {
-        <String as Type<DB>>::type_info()
+        T::type_info()
     }
 
     fn compatible(ty: &DB::TypeInfo) -> bool {
-        <String as Type<DB>>::compatible(ty)
+        T::compatible(ty)
     }
 }
 
 impl<'r, DB: Database, E, const ORDINAL: usize, T> Decode<'r, DB> for ForeignKey<E, ORDINAL, T>
 where
-    String: Decode<'r, DB>,
+    T: Decode<'r, DB>,
 {
     fn decode(
         value: <DB as sqlx::database::HasValueRef<'r>>::ValueRef,
     ) -> Result<Self, sqlx::error::BoxDynError> {
-        todo!()
+        T::decode(value).map(Self::from)
     }
 }
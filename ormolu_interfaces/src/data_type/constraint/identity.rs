@@ -1,3 +1,5 @@
+use crate::{SqlValue, ToSqlValue};
+use sqlx::{Database, Decode, prelude::Type};
 use std::ops::Deref;
 
 /// An identity column is a special column that is generated automatically from an implicit sequence.
@@ -5,12 +7,54 @@ use std::ops::Deref;
 /// It can be used to generate key values.
 ///
 /// see: <https://www.postgresql.org/docs/current/ddl-identity-columns.html#DDL-IDENTITY-COLUMNS>
+///
+/// Identity columns are left out of an INSERT so the database generates them.
 #[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub struct Identity<T>(T);
 
+impl<T> From<T> for Identity<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<DB: Database, T> Type<DB> for Identity<T>
+where
+    T: Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        T::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        T::compatible(ty)
+    }
+}
+
+impl<'r, DB: Database, T> Decode<'r, DB> for Identity<T>
+where
+    T: Decode<'r, DB>,
+{
+    fn decode(
+        value: <DB as sqlx::database::HasValueRef<'r>>::ValueRef,
+    ) -> Result<Self, sqlx::error::BoxDynError> {
+        T::decode(value).map(Self)
+    }
+}
+
 impl<T> Deref for Identity<T> {
     type Target = T;
     fn deref(&self) -> &Self::Target {
         &self.0
     }
 }
+
+impl<T> ToSqlValue for Identity<T>
+where
+    T: ToSqlValue,
+{
+    fn to_sql_value(&self) -> SqlValue {
+        self.0.to_sql_value()
+    }
+}
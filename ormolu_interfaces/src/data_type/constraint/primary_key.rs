@@ -1,4 +1,4 @@
-use crate::{HasPrimaryKey, Key};
+use crate::{HasPrimaryKey, Key, PgAcquire, SqlValue, ToSqlValue};
 use sqlx::{Database, Decode, prelude::Type};
 use std::hash::Hash;
 use std::ops::{Deref, DerefMut};
@@ -70,25 +70,25 @@ impl<E, T> From<T> for PrimaryKey<E, T> {
 
 impl<DB: Database, E, T> Type<DB> for PrimaryKey<E, T>
 where
-    String: Type<DB>,
+    T: Type<DB>,
 {
     fn type_info() -> DB::TypeInfo {
-        <String as Type<DB>>::type_info()
+        T::type_info()
     }
 
     fn compatible(ty: &DB::TypeInfo) -> bool {
-        <String as Type<DB>>::compatible(ty)
+        T::compatible(ty)
     }
 }
 
 impl<'r, DB: Database, E, T> Decode<'r, DB> for PrimaryKey<E, T>
 where
-    String: Decode<'r, DB>,
+    T: Decode<'r, DB>,
 {
     fn decode(
         value: <DB as sqlx::database::HasValueRef<'r>>::ValueRef,
     ) -> Result<Self, sqlx::error::BoxDynError> {
-        todo!()
+        T::decode(value).map(Self::from)
     }
 }
 
@@ -109,8 +109,11 @@ impl<Entity, T> Key<Entity, T> for PrimaryKey<Entity, T>
 where
     Entity: HasPrimaryKey<T>,
 {
-    fn get_entity(&self) -> impl Future<Output = Result<Option<Entity>, crate::OrmoluError>> {
-        Entity::get_by_primary_key(self)
+    fn get_entity<'c>(
+        &self,
+        db: impl PgAcquire<'c>,
+    ) -> impl Future<Output = Result<Option<Entity>, crate::OrmoluError>> + Send {
+        Entity::get_by_primary_key(db, self)
     }
 }
 
@@ -122,3 +125,12 @@ where
         write!(f, "{}", self.value)
     }
 }
+
+impl<E, T> ToSqlValue for PrimaryKey<E, T>
+where
+    T: ToSqlValue,
+{
+    fn to_sql_value(&self) -> SqlValue {
+        self.value.to_sql_value()
+    }
+}
@@ -1,3 +1,4 @@
+use crate::{SqlValue, ToSqlValue};
 use sqlx::{Database, Decode, prelude::Type};
 use std::ops::Deref;
 
@@ -38,25 +39,25 @@ pub struct Unique<T>(T);
 
 impl<DB: Database, T> Type<DB> for Unique<T>
 where
-    String: Type<DB>,
+    T: Type<DB>,
 {
     fn type_info() -> DB::TypeInfo {
-        <String as Type<DB>>::type_info()
+        T::type_info()
     }
 
     fn compatible(ty: &DB::TypeInfo) -> bool {
-        <String as Type<DB>>::compatible(ty)
+        T::compatible(ty)
     }
 }
 
 impl<'r, DB: Database, T> Decode<'r, DB> for Unique<T>
 where
-    String: Decode<'r, DB>,
+    T: Decode<'r, DB>,
 {
     fn decode(
         value: <DB as sqlx::database::HasValueRef<'r>>::ValueRef,
     ) -> Result<Self, sqlx::error::BoxDynError> {
-        todo!()
+        T::decode(value).map(Self)
     }
 }
 
@@ -78,3 +79,12 @@ impl<T> From<T> for Unique<T> {
         Self(s)
     }
 }
+
+impl<T> ToSqlValue for Unique<T>
+where
+    T: ToSqlValue,
+{
+    fn to_sql_value(&self) -> SqlValue {
+        self.0.to_sql_value()
+    }
+}
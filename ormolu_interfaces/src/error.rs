@@ -1,3 +1,4 @@
+use crate::SqlValue;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -5,6 +6,51 @@ pub enum OrmoluError {
     #[error("Database error: {0}")]
     Database(#[from] sqlx::error::Error),
 
+    /// An UPDATE or DELETE without a filter would have touched every row.
+    #[error(
+        "refusing to {statement} every row of {table} without a filter, call all_rows() to allow it"
+    )]
+    Unfiltered {
+        statement: &'static str,
+        table: &'static str,
+    },
+
+    /// An UPDATE or DELETE was built from a query with a clause it cannot
+    /// carry over, which would have silently widened it.
+    #[error("cannot {statement} {table} through a query with {clause}")]
+    UnsupportedClause {
+        statement: &'static str,
+        table: &'static str,
+        clause: &'static str,
+    },
+
+    /// The values of an `IN` list are not an array of non-NULL values of one
+    /// type, the only arrays that can be bound.
+    #[error("IN needs an array of non-NULL values of one type, found {0:?}")]
+    InvalidList(SqlValue),
+
+    /// A query expected to return exactly one row returned none.
+    #[error("expected one row of {table}, found none")]
+    NotFound { table: &'static str },
+
+    /// A foreign key references a column past the last one of its table.
+    #[error("{table} has no column {ordinal} for a foreign key to reference")]
+    NoSuchColumn { table: &'static str, ordinal: usize },
+
+    /// A query expected to return exactly one row returned more.
+    #[error("expected one row of {table}, found more than one")]
+    MultipleRows { table: &'static str },
+
+    /// Neither the [`PoolConfig`](crate::PoolConfig) nor the environment has
+    /// a URL for the database.
+    #[error("no URL for database {database}, set {variable}")]
+    MissingDatabaseUrl { database: String, variable: String },
+
+    /// The pool of the database was already opened, it can only be configured
+    /// before its first query.
+    #[error("the pool of database {database} is already in use")]
+    PoolInUse { database: String },
+
     #[error("Other custom error: {0}")]
     Other(String),
 }
@@ -0,0 +1,145 @@
+use super::{Col, FilterState};
+use crate::{
+    ToSqlValue, Unique,
+    query::{Aggregate, Expr, Where},
+};
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+/// An aggregate over a proxy field, `T` is the type its value decodes into.
+///
+/// Aggregates can be selected next to the grouped columns and compared in a
+/// HAVING filter:
+/// ```rust,ignore
+/// let per_status: Vec<(String, i64)> = orders
+///     .group_by(|o| (o.status,))
+///     .having(|o| o.id.count() > 10)
+///     .aggregate(|o| (o.status, o.id.count()))
+///     .all(&pool)
+///     .await?;
+/// ```
+pub struct Agg<T> {
+    pub expr: Expr,
+    _marker: PhantomData<T>,
+    pub state: Rc<RefCell<FilterState>>,
+}
+
+impl<T> Agg<T> {
+    fn new(expr: Expr, state: Rc<RefCell<FilterState>>) -> Self {
+        Self {
+            expr,
+            state,
+            _marker: PhantomData,
+        }
+    }
+
+    fn record(&self, clause: Where) -> bool {
+        self.state.borrow_mut().record(clause)
+    }
+}
+
+/// Column types that can be summed and averaged.
+///
+/// Postgres returns `numeric` for some sums and averages, which we cannot
+/// decode without an extra dependency, so the result is cast to `Sum`.
+pub trait Summable {
+    type Sum;
+    /// The SQL type `sum` is cast to
+    const SUM_TYPE: &'static str;
+}
+
+macro_rules! impl_summable {
+    ($($ty:ty => $sum:ty, $sql_type:literal),* $(,)?) => {
+        $(
+            impl Summable for $ty {
+                type Sum = $sum;
+                const SUM_TYPE: &'static str = $sql_type;
+            }
+        )*
+    };
+}
+
+impl_summable! {
+    i16 => i64, "bigint",
+    i32 => i64, "bigint",
+    i64 => i64, "bigint",
+    f32 => f64, "double precision",
+    f64 => f64, "double precision",
+}
+
+impl<T: Summable> Summable for Option<T> {
+    type Sum = T::Sum;
+    const SUM_TYPE: &'static str = T::SUM_TYPE;
+}
+
+impl<T: Summable> Summable for Unique<T> {
+    type Sum = T::Sum;
+    const SUM_TYPE: &'static str = T::SUM_TYPE;
+}
+
+impl<T> Col<T> {
+    /// `count(column)`, the number of rows where the column is not NULL.
+    pub fn count(&self) -> Agg<i64> {
+        Agg::new(
+            Expr::aggregate(Aggregate::Count, self.column_ref()),
+            self.state.clone(),
+        )
+    }
+
+    /// `min(column)`, NULL when the group has no values.
+    pub fn min(&self) -> Agg<Option<T>> {
+        Agg::new(
+            Expr::aggregate(Aggregate::Min, self.column_ref()),
+            self.state.clone(),
+        )
+    }
+
+    /// `max(column)`, NULL when the group has no values.
+    pub fn max(&self) -> Agg<Option<T>> {
+        Agg::new(
+            Expr::aggregate(Aggregate::Max, self.column_ref()),
+            self.state.clone(),
+        )
+    }
+}
+
+impl<T: Summable> Col<T> {
+    /// `sum(column)`, NULL when the group has no values.
+    pub fn sum(&self) -> Agg<Option<T::Sum>> {
+        Agg::new(
+            Expr::aggregate(Aggregate::Sum, self.column_ref()).cast(T::SUM_TYPE),
+            self.state.clone(),
+        )
+    }
+
+    /// `avg(column)` as a double, NULL when the group has no values.
+    pub fn avg(&self) -> Agg<Option<f64>> {
+        Agg::new(
+            Expr::aggregate(Aggregate::Avg, self.column_ref()).cast("double precision"),
+            self.state.clone(),
+        )
+    }
+}
+
+impl PartialEq<i64> for Agg<i64> {
+    fn eq(&self, other: &i64) -> bool {
+        self.record(Where::eq(self.expr.clone(), other))
+    }
+}
+
+impl_traced_ord! {
+    impl[] PartialOrd<i64> for Agg<i64>
+    => |this, oper, other| this.record(Where::new(oper, this.expr.clone(), other.to_sql_value()))
+}
+
+impl<V: ToSqlValue> PartialEq<V> for Agg<Option<V>> {
+    fn eq(&self, other: &V) -> bool {
+        self.record(Where::eq(self.expr.clone(), other))
+    }
+}
+
+impl_traced_ord! {
+    impl[V: ToSqlValue] PartialOrd<V> for Agg<Option<V>>
+    => |this, oper, other| this.record(Where::new(oper, this.expr.clone(), other.to_sql_value()))
+}
@@ -0,0 +1,172 @@
+use super::{Col, ColumnValue, FilterState};
+use crate::{
+    ToSqlValue,
+    query::{ArithOp, Expr, Where, WhereOp},
+};
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::ops::{Add, Div, Mul, Sub};
+use std::rc::Rc;
+
+/// A value computed from columns for every row, `V` is the type it evaluates to.
+///
+/// Arithmetic on proxy fields builds one, and it compares like a column:
+/// ```rust,ignore
+/// let low_stock = products.filter(|p| p.stock_quantity * 2 < p.reorder_level);
+/// ```
+///
+/// The operators take the field by value, borrow it to use it more than once:
+/// ```rust,ignore
+/// let margin = products.filter(|p| (&p.price - &p.cost) / &p.price > 0.3);
+/// ```
+pub struct Computed<V> {
+    pub expr: Expr,
+    _marker: PhantomData<V>,
+    pub state: Rc<RefCell<FilterState>>,
+}
+
+impl<V> Computed<V> {
+    fn new(expr: Expr, state: Rc<RefCell<FilterState>>) -> Self {
+        Self {
+            expr,
+            state,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// The right side of a comparison or arithmetic on a column holding `V`: a
+/// value, another column holding `V` or a [`Computed`] value.
+pub trait ToExpr<V> {
+    fn to_expr(&self) -> Expr;
+}
+
+impl<V: ToSqlValue> ToExpr<V> for V {
+    fn to_expr(&self) -> Expr {
+        Expr::Value(self.to_sql_value())
+    }
+}
+
+impl ToExpr<String> for &str {
+    fn to_expr(&self) -> Expr {
+        Expr::Value(self.to_sql_value())
+    }
+}
+
+impl<T: ColumnValue> ToExpr<T::Value> for Col<T> {
+    fn to_expr(&self) -> Expr {
+        self.column_ref().into()
+    }
+}
+
+impl<V> ToExpr<V> for Computed<V> {
+    fn to_expr(&self) -> Expr {
+        self.expr.clone()
+    }
+}
+
+impl<T: ColumnValue> ToExpr<T::Value> for &Col<T> {
+    fn to_expr(&self) -> Expr {
+        self.column_ref().into()
+    }
+}
+
+impl<V> ToExpr<V> for &Computed<V> {
+    fn to_expr(&self) -> Expr {
+        self.expr.clone()
+    }
+}
+
+/// Value types that support `+`, `-`, `*` and `/`.
+pub trait Numeric {}
+
+impl Numeric for i16 {}
+impl Numeric for i32 {}
+impl Numeric for i64 {}
+impl Numeric for f32 {}
+impl Numeric for f64 {}
+
+macro_rules! impl_arithmetic {
+    ($($trait:ident $method:ident => $op:ident),* $(,)?) => {
+        $(
+            impl<T, R> $trait<R> for Col<T>
+            where
+                T: ColumnValue,
+                T::Value: Numeric,
+                R: ToExpr<T::Value>,
+            {
+                type Output = Computed<T::Value>;
+
+                fn $method(self, rhs: R) -> Self::Output {
+                    (&self).$method(rhs)
+                }
+            }
+
+            impl<T, R> $trait<R> for &Col<T>
+            where
+                T: ColumnValue,
+                T::Value: Numeric,
+                R: ToExpr<T::Value>,
+            {
+                type Output = Computed<T::Value>;
+
+                fn $method(self, rhs: R) -> Self::Output {
+                    let expr = Expr::binary(self.column_ref(), ArithOp::$op, rhs.to_expr());
+                    Computed::new(expr, self.state.clone())
+                }
+            }
+
+            impl<V, R> $trait<R> for Computed<V>
+            where
+                V: Numeric,
+                R: ToExpr<V>,
+            {
+                type Output = Computed<V>;
+
+                fn $method(self, rhs: R) -> Self::Output {
+                    let expr = Expr::binary(self.expr, ArithOp::$op, rhs.to_expr());
+                    Computed::new(expr, self.state)
+                }
+            }
+
+            impl<V, R> $trait<R> for &Computed<V>
+            where
+                V: Numeric,
+                R: ToExpr<V>,
+            {
+                type Output = Computed<V>;
+
+                fn $method(self, rhs: R) -> Self::Output {
+                    let expr = Expr::binary(self.expr.clone(), ArithOp::$op, rhs.to_expr());
+                    Computed::new(expr, self.state.clone())
+                }
+            }
+        )*
+    };
+}
+
+impl_arithmetic! {
+    Add add => Add,
+    Sub sub => Sub,
+    Mul mul => Mul,
+    Div div => Div,
+}
+
+impl<V> Computed<V> {
+    fn record(&self, oper: WhereOp, other: Expr) -> bool {
+        self.state
+            .borrow_mut()
+            .record(Where::new(oper, self.expr.clone(), other))
+    }
+}
+
+impl<V, R: ToExpr<V>> PartialEq<R> for Computed<V> {
+    fn eq(&self, other: &R) -> bool {
+        self.record(WhereOp::EqualTo, other.to_expr())
+    }
+}
+
+impl_traced_ord! {
+    impl[V, R: ToExpr<V>] PartialOrd<R> for Computed<V>
+    => |this, oper, other| this.record(oper, other.to_expr())
+}
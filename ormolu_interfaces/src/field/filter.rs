@@ -1,28 +1,67 @@
-use crate::{Table, query::Where};
+use crate::{
+    SqlValue, Table,
+    query::{ColRef, Q, QueryState, Subquery, Where},
+};
 use std::{cell::RefCell, rc::Rc};
 
 #[derive(Default)]
 pub struct FilterState {
-    pub index: usize,
-    /// This is the bool returned from each filter statement in the predicate
-    pub returns: Vec<bool>,
-    /// These are the actual conditions created
+    /// What each condition evaluated by the predicate returns, in evaluation
+    /// order. Conditions past the end of this list return `true`.
+    pub decisions: Vec<bool>,
+    /// The conditions the predicate evaluated during this run
     pub clauses: Vec<Where>,
+    /// Columns given a new value with [`Col::set`](crate::Col::set)
+    pub assignments: Vec<(&'static str, SqlValue)>,
+    /// Set for the proxy of a referenced table, its conditions are recorded
+    /// on the proxy holding the foreign key
+    pub relation: Option<Relation>,
 }
 
 impl FilterState {
-    pub fn return_true(&mut self) -> bool {
-        self.index += 1;
-
-        // if already exists in order then return it
-        //
-        // bool is flipped after running predicate outside
-        if let Some(ret) = self.returns.get(self.index - 1) {
-            return *ret;
+    /// Records a condition evaluated by the predicate and returns the result the
+    /// predicate should see for it.
+    pub fn record(&mut self, clause: Where) -> bool {
+        if let Some(relation) = &self.relation {
+            return relation.record(clause);
         }
 
-        self.returns.push(true);
-        true
+        let index = self.clauses.len();
+        self.clauses.push(clause);
+
+        self.decisions.get(index).copied().unwrap_or(true)
+    }
+}
+
+/// A foreign key followed with [`Col::related`](crate::Col::related).
+pub struct Relation {
+    /// The state of the proxy holding the foreign key
+    pub parent: Rc<RefCell<FilterState>>,
+    pub foreign_key: ColRef,
+    /// Whether the foreign key can be NULL
+    pub nullable: bool,
+    /// Qualified `schema.object` name of the referenced table
+    pub table: &'static str,
+    /// The column the foreign key references
+    pub referenced: ColRef,
+}
+
+impl Relation {
+    /// Records `foreign_key IN (SELECT referenced FROM table WHERE clause)` on
+    /// the parent in place of the clause.
+    fn record(&self, clause: Where) -> bool {
+        let mut statement =
+            QueryState::new_select_from(self.table, vec![self.referenced.clone().into()]);
+        statement.and_where(clause);
+
+        let clause = Where::in_subquery(self.foreign_key.clone(), Subquery::<()>::new(statement));
+        let clause = if self.nullable {
+            clause.nullable()
+        } else {
+            clause
+        };
+
+        self.parent.borrow_mut().record(clause)
     }
 }
 
@@ -38,12 +77,16 @@ impl FilterState {
 /// .filter(|order| !order.name.contains("jon") && order.id == 2)
 /// ```
 ///
-/// To get around this we store the bool for each statement within a filter
-/// predicate and will flip return values to avoid short circuits done by negation.
-/// Allowing the example above to reach the end.
-#[derive(Default)]
+/// To get around this every comparison on the proxy records its condition and
+/// returns a decision that we control. The predicate is re-run with every
+/// combination of decisions it can actually reach, which gives a decision tree
+/// of the short circuit paths. That tree is then folded back into the
+/// equivalent boolean formula, so `a && (b || c)` becomes `a AND (b OR c)`.
+///
+/// The number of runs grows with the number of paths through the predicate,
+/// which is fine for the handful of conditions a filter usually has.
 pub struct FieldsFilter {
-    pub state: Rc<RefCell<FilterState>>,
+    pub condition: Q,
 }
 
 impl FieldsFilter {
@@ -52,27 +95,90 @@ impl FieldsFilter {
         T: Table,
         P: Fn(T::Proxy) -> bool,
     {
-        let this = Self::default();
+        let condition = Self::explore::<T, P>(&predicate, Vec::new());
+        Self { condition }
+    }
 
-        let proxy = T::to_field_filter(this.state.clone());
-        let mut positive_context = predicate(proxy);
+    /// Runs the predicate with the given decisions and branches on the first
+    /// condition it evaluates after them.
+    fn explore<T, P>(predicate: &P, decisions: Vec<bool>) -> Q
+    where
+        T: Table,
+        P: Fn(T::Proxy) -> bool,
+    {
+        let state = Rc::new(RefCell::new(FilterState {
+            decisions: decisions.clone(),
+            ..Default::default()
+        }));
+
+        let result = predicate(T::to_field_filter(state.clone()));
+        let mut clauses = std::mem::take(&mut state.borrow_mut().clauses);
+
+        if clauses.len() <= decisions.len() {
+            return if result { Q::TRUE } else { Q::FALSE };
+        }
 
-        while !positive_context {
-            {
-                let mut state = this.state.borrow_mut();
+        let clause = clauses.swap_remove(decisions.len());
 
-                if let Some(b) = state.returns.pop() {
-                    state.returns.push(!b);
-                }
+        let mut when_true = decisions.clone();
+        when_true.push(true);
+        let mut when_false = decisions;
+        when_false.push(false);
+
+        if_then_else(
+            clause,
+            Self::explore::<T, P>(predicate, when_true),
+            Self::explore::<T, P>(predicate, when_false),
+        )
+    }
+}
 
-                state.clauses.clear();
-                state.index = 0;
-            } // drop borrow_mut
+/// Folds one node of the decision tree, `if cond { then } else { otherwise }`,
+/// into the simplest formula we can find for it.
+fn if_then_else(cond: Where, then: Q, otherwise: Q) -> Q {
+    let cond = Q::from(cond);
 
-            let proxy2 = T::to_field_filter(this.state.clone());
-            positive_context = predicate(proxy2);
+    if then == otherwise {
+        return then;
+    }
+
+    match (then, otherwise) {
+        (t, f) if t == Q::TRUE && f == Q::FALSE => cond,
+        (t, f) if t == Q::FALSE && f == Q::TRUE => !cond,
+        (t, f) if t == Q::TRUE => cond.or(f),
+        (t, f) if t == Q::FALSE => (!cond).and(f),
+        (t, f) if f == Q::TRUE => (!cond).or(t),
+        (t, f) if f == Q::FALSE => cond.and(t),
+        (t, f) => {
+            // `(a && b) || c` -> then is `b OR c`, otherwise is `c`
+            if let Some(rest) = strip(t.or_list(), &f.or_list()) {
+                return cond.and(Q::any(rest)).or(f);
+            }
+            // `(a || b) && c` -> then is `c`, otherwise is `b AND c`
+            if let Some(rest) = strip(f.and_list(), &t.and_list()) {
+                return cond.or(Q::all(rest)).and(t);
+            }
+            // `(!a && b) || c` -> then is `c`, otherwise is `b OR c`
+            if let Some(rest) = strip(f.or_list(), &t.or_list()) {
+                return (!cond).and(Q::any(rest)).or(t);
+            }
+            // `(!a || b) && c` -> then is `b AND c`, otherwise is `c`
+            if let Some(rest) = strip(t.and_list(), &f.and_list()) {
+                return (!cond).or(Q::all(rest)).and(f);
+            }
+
+            cond.clone().and(t).or((!cond).and(f))
         }
+    }
+}
 
-        this
+/// Removes every condition of `part` from `whole`, returns `None` if `whole`
+/// does not contain all of them.
+fn strip(mut whole: Vec<Q>, part: &[Q]) -> Option<Vec<Q>> {
+    for q in part {
+        let index = whole.iter().position(|w| w == q)?;
+        whole.remove(index);
     }
+
+    Some(whole)
 }
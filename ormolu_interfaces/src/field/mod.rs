@@ -1,7 +1,60 @@
+//! Proxy fields handed to the filter, order and select closures.
+//!
+//! Comparing a proxy records a condition on the query instead of comparing
+//! values, so the `PartialEq` and `PartialOrd` impls here are not orderings:
+//! `partial_cmp` has no condition to record and always returns `None`, and
+//! `a < b` and `!(a >= b)` record different conditions.
+
+/// Implements `PartialOrd` by recording `<`, `<=`, `>` and `>=` through
+/// `|this, oper, other| record`.
+macro_rules! impl_traced_ord {
+    (
+        $(#[$attr:meta])*
+        impl[$($generics:tt)*] PartialOrd<$rhs:ty> for $ty:ty
+        $(where [$($bounds:tt)*])?
+        => |$this:ident, $oper:ident, $other:ident| $record:expr
+    ) => {
+        $(#[$attr])*
+        impl<$($generics)*> PartialOrd<$rhs> for $ty $(where $($bounds)*)? {
+            fn partial_cmp(&self, _: &$rhs) -> Option<std::cmp::Ordering> {
+                None
+            }
+
+            fn lt(&self, other: &$rhs) -> bool {
+                let ($this, $oper, $other) = (self, $crate::query::WhereOp::LessThan, other);
+                $record
+            }
+
+            fn le(&self, other: &$rhs) -> bool {
+                let ($this, $oper, $other) =
+                    (self, $crate::query::WhereOp::LessThanOrEqualTo, other);
+                $record
+            }
+
+            fn gt(&self, other: &$rhs) -> bool {
+                let ($this, $oper, $other) = (self, $crate::query::WhereOp::GreaterThan, other);
+                $record
+            }
+
+            fn ge(&self, other: &$rhs) -> bool {
+                let ($this, $oper, $other) =
+                    (self, $crate::query::WhereOp::GreaterThanOrEqualTo, other);
+                $record
+            }
+        }
+    };
+}
+
+pub mod aggregate;
+pub mod expression;
 pub mod filter;
 pub mod types;
 
+use crate::ToSqlValue;
+use crate::query::ColRef;
 pub use crate::query::QueryState;
+pub use aggregate::*;
+pub use expression::*;
 pub use filter::*;
 #[allow(unused_imports)]
 pub use types::*;
@@ -11,17 +64,49 @@ use std::marker::PhantomData;
 use std::rc::Rc;
 
 pub struct Col<T> {
-    pub field_name: &'static str,
+    /// Qualified `schema.object` name of the table the column is on
+    pub table: &'static str,
+    /// Database name of the column, `#[gild(column = "...")]` if it is renamed
+    pub column_name: &'static str,
     _marker: PhantomData<T>,
     pub state: Rc<RefCell<FilterState>>,
 }
 
 impl<T> Col<T> {
-    pub fn new(field_name: &'static str, state: Rc<RefCell<FilterState>>) -> Self {
+    pub fn new(
+        table: &'static str,
+        column_name: &'static str,
+        state: Rc<RefCell<FilterState>>,
+    ) -> Self {
         Self {
-            field_name,
+            table,
+            column_name,
             state,
             _marker: PhantomData,
         }
     }
+
+    /// Sets the column to `value` in an UPDATE.
+    ///
+    /// ```rust,ignore
+    /// orders.filter(|o| o.status == "paid").update(&pool, |o| {
+    ///     o.status.set("shipped".to_string());
+    ///     o.shipped_at.set(Some(now));
+    /// });
+    /// ```
+    pub fn set(&self, value: impl Into<T>)
+    where
+        T: ToSqlValue,
+    {
+        let value = value.into().to_sql_value();
+        self.state
+            .borrow_mut()
+            .assignments
+            .push((self.column_name, value));
+    }
+
+    /// The column this proxy field stands for.
+    pub fn column_ref(&self) -> ColRef {
+        ColRef::new(self.table, self.column_name)
+    }
 }
@@ -0,0 +1,18 @@
+use super::ColumnValue;
+use crate::{ForeignKey, Identity, PrimaryKey, Unique};
+
+impl<E, T: ColumnValue> ColumnValue for PrimaryKey<E, T> {
+    type Value = T::Value;
+}
+
+impl<E, const C: usize, T: ColumnValue> ColumnValue for ForeignKey<E, C, T> {
+    type Value = T::Value;
+}
+
+impl<T: ColumnValue> ColumnValue for Unique<T> {
+    type Value = T::Value;
+}
+
+impl<T: ColumnValue> ColumnValue for Identity<T> {
+    type Value = T::Value;
+}
@@ -0,0 +1,72 @@
+use super::Nullable;
+use crate::{
+    Col, SqlValue, ToSqlValue,
+    query::{Operand, Subquery, Where},
+};
+
+/// Values a column can be checked against with [`Col::is_in`], `V` is the
+/// value type of the column.
+pub trait InList<V> {
+    fn into_operand(self) -> Operand;
+}
+
+impl<V: ToSqlValue> InList<V> for &[V] {
+    fn into_operand(self) -> Operand {
+        SqlValue::Array(self.iter().map(ToSqlValue::to_sql_value).collect()).into()
+    }
+}
+
+impl<V: ToSqlValue, const N: usize> InList<V> for &[V; N] {
+    fn into_operand(self) -> Operand {
+        self.as_slice().into_operand()
+    }
+}
+
+impl<V: ToSqlValue> InList<V> for &Vec<V> {
+    fn into_operand(self) -> Operand {
+        self.as_slice().into_operand()
+    }
+}
+
+impl<V> InList<V> for Subquery<V> {
+    fn into_operand(self) -> Operand {
+        self.into()
+    }
+}
+
+impl<V> InList<V> for &Subquery<V> {
+    fn into_operand(self) -> Operand {
+        self.untyped().into()
+    }
+}
+
+impl<T: Nullable> Col<T> {
+    /// `column = ANY($1)` with the values bound as one array, or
+    /// `column IN (SELECT ...)` for a [`Subquery`].
+    ///
+    /// ```rust,ignore
+    /// let orders = orders.filter(|o| o.status.is_in(&["paid".to_string(), "shipped".to_string()]));
+    /// let orders = orders.filter(|o| !o.customer_id.is_in(&blocked));
+    /// ```
+    ///
+    /// Negating it gives `<> ALL($1)` or `NOT IN`, which on a nullable column
+    /// also matches the NULL rows.
+    ///
+    /// # Panics
+    /// When the values convert to different [`SqlValue`] variants, which only
+    /// a [`ColumnValue`](super::ColumnValue) with a hand written
+    /// [`ToSqlValue`] can do, see [`Where::is_in`].
+    pub fn is_in(&self, values: impl InList<T::Value>) -> bool {
+        let clause = match Where::is_in(self.column_ref(), values.into_operand()) {
+            Ok(clause) => clause,
+            Err(error) => panic!("{error}"),
+        };
+        let clause = if T::NULLABLE {
+            clause.nullable()
+        } else {
+            clause
+        };
+
+        self.state.borrow_mut().record(clause)
+    }
+}
@@ -1,13 +1,100 @@
 #![allow(unused_imports)]
 
+use crate::{
+    Col, ToExpr, ToSqlValue,
+    query::{Where, WhereOp},
+};
+use sqlx::types::chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+
 mod string;
 pub use string::*;
 
-mod integer;
-pub use integer::*;
-
 mod boolean;
 pub use boolean::*;
 
-mod primary_key;
-pub use primary_key::*;
+mod constraint;
+pub use constraint::*;
+
+mod option;
+pub use option::*;
+
+mod membership;
+pub use membership::*;
+
+mod relation;
+pub use relation::*;
+
+/// A column type that can be compared to a value in a filter.
+///
+/// `Value` is what the column holds once the constraint wrappers are looked
+/// through, so a `Col<PrimaryKey<Self, i32>>` is compared to a plain `i32` and
+/// the literal in `o.id == 2` takes its type from the column.
+pub trait ColumnValue {
+    type Value: ToSqlValue;
+}
+
+macro_rules! impl_column_value {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl ColumnValue for $ty {
+                type Value = $ty;
+            }
+        )*
+    };
+}
+
+impl_column_value! {
+    bool,
+    i16,
+    i32,
+    i64,
+    f32,
+    f64,
+    String,
+    Vec<u8>,
+    NaiveDate,
+    NaiveTime,
+    NaiveDateTime,
+    DateTime<Utc>,
+}
+
+/// A [`ColumnValue`] or an `Option` of one, the value type of a column that
+/// might be NULL.
+pub trait Nullable {
+    type Value: ToSqlValue;
+    /// Whether the column can be NULL, see [`Where::nullable`]
+    const NULLABLE: bool;
+}
+
+impl<T: ColumnValue> Nullable for T {
+    type Value = T::Value;
+    const NULLABLE: bool = false;
+}
+
+impl<T: ColumnValue> Nullable for Option<T> {
+    type Value = T::Value;
+    const NULLABLE: bool = true;
+}
+
+/// Columns compare to a value of their type, another column or a
+/// [`Computed`](crate::Computed) value, `o.updated_at > o.created_at` is
+/// written `"updated_at" > "created_at"`.
+impl<T: ColumnValue, R: ToExpr<T::Value>> PartialEq<R> for Col<T> {
+    fn eq(&self, other: &R) -> bool {
+        self.record(WhereOp::EqualTo, other)
+    }
+}
+
+impl_traced_ord! {
+    /// Negating a comparison flips its operator, `!(a > b)` is written `a <= b`.
+    impl[T: ColumnValue, R: ToExpr<T::Value>] PartialOrd<R> for Col<T>
+    => |this, oper, other| this.record(oper, other)
+}
+
+impl<T: ColumnValue> Col<T> {
+    fn record(&self, oper: WhereOp, other: &impl ToExpr<T::Value>) -> bool {
+        self.state
+            .borrow_mut()
+            .record(Where::new(oper, self.column_ref(), other.to_expr()))
+    }
+}
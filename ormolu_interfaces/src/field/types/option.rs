@@ -0,0 +1,101 @@
+use super::ColumnValue;
+use crate::{
+    Col, Computed, ToExpr, ToSqlValue,
+    query::{Expr, Where, WhereOp},
+};
+
+impl<T> Col<Option<T>> {
+    /// `column IS NULL`
+    pub fn is_null(&self) -> bool {
+        self.state
+            .borrow_mut()
+            .record(Where::is_null(self.column_ref()))
+    }
+
+    /// `column IS NOT NULL`
+    pub fn is_not_null(&self) -> bool {
+        self.state
+            .borrow_mut()
+            .record(Where::is_not_null(self.column_ref()))
+    }
+}
+
+/// The right side of a comparison on a nullable column holding `V`: whatever a
+/// column holding `V` compares to, or an `Option<V>`.
+pub trait ToNullableExpr<V> {
+    /// `None` stands for NULL
+    fn to_nullable_expr(&self) -> Option<Expr>;
+}
+
+impl<V: ToSqlValue> ToNullableExpr<V> for Option<V> {
+    fn to_nullable_expr(&self) -> Option<Expr> {
+        self.as_ref().map(|value| Expr::Value(value.to_sql_value()))
+    }
+}
+
+impl<V: ToSqlValue> ToNullableExpr<V> for V {
+    fn to_nullable_expr(&self) -> Option<Expr> {
+        Some(self.to_expr())
+    }
+}
+
+impl ToNullableExpr<String> for &str {
+    fn to_nullable_expr(&self) -> Option<Expr> {
+        Some(ToExpr::<String>::to_expr(self))
+    }
+}
+
+impl<T: ColumnValue> ToNullableExpr<T::Value> for Col<T> {
+    fn to_nullable_expr(&self) -> Option<Expr> {
+        Some(self.to_expr())
+    }
+}
+
+impl<T: ColumnValue> ToNullableExpr<T::Value> for &Col<T> {
+    fn to_nullable_expr(&self) -> Option<Expr> {
+        Some(self.to_expr())
+    }
+}
+
+impl<V> ToNullableExpr<V> for Computed<V> {
+    fn to_nullable_expr(&self) -> Option<Expr> {
+        Some(self.to_expr())
+    }
+}
+
+impl<V> ToNullableExpr<V> for &Computed<V> {
+    fn to_nullable_expr(&self) -> Option<Expr> {
+        Some(self.to_expr())
+    }
+}
+
+/// `== None` is written `IS NULL` and `== Some(value)` is written `= value`.
+///
+/// Like comparing `Option`s in Rust, a NULL column is not equal to any value,
+/// so `!(c.phone_number == Some(number))` also matches rows without a phone number.
+impl<T: ColumnValue, R: ToNullableExpr<T::Value>> PartialEq<R> for Col<Option<T>> {
+    fn eq(&self, other: &R) -> bool {
+        let clause = match other.to_nullable_expr() {
+            Some(expr) => Where::new(WhereOp::EqualTo, self.column_ref(), expr).nullable(),
+            None => Where::is_null(self.column_ref()),
+        };
+
+        self.state.borrow_mut().record(clause)
+    }
+}
+
+impl_traced_ord! {
+    /// A NULL column is neither less nor greater than a value, so
+    /// `!(c.discount > 10)` is written `("discount" <= $1 OR "discount" IS NULL)`.
+    impl[T, R] PartialOrd<R> for Col<Option<T>>
+    where [T: ColumnValue, R: ToExpr<T::Value> + ToNullableExpr<T::Value>]
+    => |this, oper, other| this.record_nullable(oper, other)
+}
+
+impl<T: ColumnValue> Col<Option<T>> {
+    fn record_nullable(&self, oper: WhereOp, other: &impl ToExpr<T::Value>) -> bool {
+        self.state
+            .borrow_mut()
+            .record(Where::new(oper, self.column_ref(), other.to_expr()).nullable())
+    }
+}
@@ -0,0 +1,131 @@
+use super::Nullable;
+use crate::{
+    Col, Command, FieldsFilter, FilterState, Filterable, ForeignKey, HasFields, HasQualifiedName,
+    Identity, PrimaryKey, Related, Relation, Table,
+    query::{ColRef, QueryState, Subquery, Where},
+};
+use std::{cell::RefCell, rc::Rc};
+
+/// A foreign key column, `Entity` is the table it references and `ORDINAL`
+/// the position of the referenced column there.
+pub trait References {
+    type Entity: Table;
+    const ORDINAL: usize;
+}
+
+impl<E: Table, const C: usize, T> References for ForeignKey<E, C, T> {
+    type Entity = E;
+    const ORDINAL: usize = C;
+}
+
+impl<T: References> References for Option<T> {
+    type Entity = T::Entity;
+    const ORDINAL: usize = T::ORDINAL;
+}
+
+/// The primary key of `Entity`, the column other tables reference.
+pub trait Referenced {
+    type Entity: Table;
+}
+
+impl<E: Table, T> Referenced for PrimaryKey<E, T> {
+    type Entity = E;
+}
+
+impl<T: Referenced> Referenced for Identity<T> {
+    type Entity = T::Entity;
+}
+
+impl<T: References + Nullable> Col<T> {
+    /// The row the foreign key references, comparing its fields filters on it.
+    ///
+    /// ```rust,ignore
+    /// // ... WHERE "customer_id" IN (SELECT "public"."customer"."id" FROM "public"."customer"
+    /// //     WHERE "public"."customer"."email" = $1)
+    /// let orders = orders.filter(|o| o.customer_id.related().email == "ann@example.com");
+    /// ```
+    ///
+    /// Every condition becomes an `IN` subquery, which Postgres plans as a semi
+    /// join, so unlike a JOIN no row is returned twice. A foreign key references
+    /// at most one row, so the conditions all apply to that same row.
+    pub fn related(&self) -> <T::Entity as Filterable>::Proxy {
+        let table = T::Entity::qualified_name();
+        let referenced = T::Entity::column(T::ORDINAL)
+            .expect("foreign key ordinal is past the last column of the referenced table");
+
+        let state = FilterState {
+            relation: Some(Relation {
+                parent: self.state.clone(),
+                foreign_key: self.column_ref(),
+                nullable: T::NULLABLE,
+                table,
+                referenced: ColRef::new(table, referenced),
+            }),
+            ..Default::default()
+        };
+
+        T::Entity::to_field_filter(Rc::new(RefCell::new(state)))
+    }
+}
+
+impl<T: Referenced> Col<T> {
+    /// Whether any row of `R` referencing this row matches the predicate, the
+    /// reverse of [`Col::related`].
+    ///
+    /// ```rust,ignore
+    /// // customers with an order over 100
+    /// let customers = customers.filter(|c| c.id.any::<Order>(|o| o.total_amount > 100.0));
+    /// ```
+    ///
+    /// All conditions of the predicate have to match the same row of `R`, which
+    /// is joined on the foreign key [`Related`] uses. Without a single foreign
+    /// key to this table, pick it with [`Col::any_via`].
+    pub fn any<R>(&self, predicate: impl Fn(R::Proxy) -> bool) -> bool
+    where
+        R: Related<T::Entity>,
+    {
+        let (foreign_key, key) = R::join_columns();
+        self.any_on::<R>(foreign_key, key, predicate)
+    }
+
+    /// Like [`Col::any`], following the foreign key column `foreign_key` picks,
+    /// for tables with more than one foreign key to this table or one to itself.
+    ///
+    /// ```rust,ignore
+    /// // customers with an invoice shipped to them
+    /// let customers = customers.filter(|c| {
+    ///     c.id.any_via::<Invoice, _>(|i| i.shipping_customer_id, |i| i.total > 100.0)
+    /// });
+    /// ```
+    pub fn any_via<R, K>(
+        &self,
+        foreign_key: impl FnOnce(R::Proxy) -> Col<K>,
+        predicate: impl Fn(R::Proxy) -> bool,
+    ) -> bool
+    where
+        R: Table,
+        K: References<Entity = T::Entity>,
+    {
+        let foreign_key = foreign_key(R::to_field_filter(Default::default())).column_ref();
+        self.any_on::<R>(foreign_key, self.column_ref(), predicate)
+    }
+
+    fn any_on<R: Table>(
+        &self,
+        foreign_key: ColRef,
+        key: ColRef,
+        predicate: impl Fn(R::Proxy) -> bool,
+    ) -> bool {
+        let mut statement = QueryState::<R>::new_select();
+        statement.and_where(FieldsFilter::new::<R, _>(predicate).condition);
+        // a single NULL would make every `NOT IN` unknown
+        statement.and_where(Where::is_not_null(foreign_key.clone()));
+        statement.command = Command::Select {
+            columns: vec![foreign_key.into()],
+        };
+
+        self.state
+            .borrow_mut()
+            .record(Where::in_subquery(key, Subquery::<()>::new(statement)))
+    }
+}
@@ -1,44 +1,108 @@
+use super::ColumnValue;
 pub use crate::query::QueryState;
-use crate::{Col, query::Where};
+use crate::{Char, Col, ForeignKey, PrimaryKey, Unique, VarChar, query::Where};
 
-impl Col<String> {
-    pub fn contains(&self, s: &str) -> bool {
-        let mut state = self.state.borrow_mut();
-        let ret = state.return_true();
-        let mut w = Where::like(self.field_name, format!("%{s}%"));
-        if !ret {
-            w = w.wrap_not();
-        }
+impl ColumnValue for &str {
+    type Value = String;
+}
 
-        state.clauses.push(w);
-        ret
-    }
+impl<const LENGTH: usize> ColumnValue for VarChar<LENGTH> {
+    type Value = String;
 }
 
-impl PartialEq<String> for Col<String> {
-    fn eq(&self, other: &String) -> bool {
-        let mut state = self.state.borrow_mut();
-        let ret = state.return_true();
-        let mut w = Where::eq(self.field_name, other.to_string());
-        if !ret {
-            w = w.wrap_not();
-        }
+impl<const N: usize> ColumnValue for Char<N> {
+    type Value = String;
+}
 
-        state.clauses.push(w);
-        ret
-    }
+/// A column holding text, which gets the string matching operators.
+pub trait TextColumn {
+    /// Whether the column can be NULL, see [`Where::nullable`]
+    const NULLABLE: bool = false;
 }
 
-impl PartialEq<String> for Col<&str> {
-    fn eq(&self, other: &String) -> bool {
-        let mut state = self.state.borrow_mut();
-        let ret = state.return_true();
-        let mut w = Where::eq(self.field_name, other.to_string());
-        if !ret {
-            w = w.wrap_not();
+impl TextColumn for String {}
+impl TextColumn for &str {}
+impl<const LENGTH: usize> TextColumn for VarChar<LENGTH> {}
+impl<const N: usize> TextColumn for Char<N> {}
+impl<T: TextColumn> TextColumn for Unique<T> {}
+impl<E, T: TextColumn> TextColumn for PrimaryKey<E, T> {}
+impl<E, const C: usize, T: TextColumn> TextColumn for ForeignKey<E, C, T> {}
+
+impl<T: TextColumn> TextColumn for Option<T> {
+    const NULLABLE: bool = true;
+}
+
+/// Escapes the LIKE wildcards `%` and `_` and the escape character `\` itself,
+/// so the text only ever matches literally.
+fn escape_like(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
         }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+impl<T: TextColumn> Col<T> {
+    /// `column LIKE '%s%'`, `s` is matched literally.
+    pub fn contains(&self, s: &str) -> bool {
+        self.record_text(Where::like(
+            self.column_ref(),
+            format!("%{}%", escape_like(s)),
+        ))
+    }
+
+    /// `column ILIKE '%s%'`, a case-insensitive [`Col::contains`].
+    pub fn icontains(&self, s: &str) -> bool {
+        self.record_text(Where::ilike(
+            self.column_ref(),
+            format!("%{}%", escape_like(s)),
+        ))
+    }
+
+    /// `column LIKE 's%'`, `s` is matched literally.
+    pub fn starts_with(&self, s: &str) -> bool {
+        self.record_text(Where::like(
+            self.column_ref(),
+            format!("{}%", escape_like(s)),
+        ))
+    }
+
+    /// `column LIKE '%s'`, `s` is matched literally.
+    pub fn ends_with(&self, s: &str) -> bool {
+        self.record_text(Where::like(
+            self.column_ref(),
+            format!("%{}", escape_like(s)),
+        ))
+    }
+
+    /// `column ~ pattern`, true when a POSIX regular expression matches part
+    /// of the column.
+    pub fn regex(&self, pattern: &str) -> bool {
+        self.record_text(Where::regex(self.column_ref(), pattern))
+    }
+
+    /// `column ~* pattern`, a case-insensitive [`Col::regex`].
+    pub fn iregex(&self, pattern: &str) -> bool {
+        self.record_text(Where::iregex(self.column_ref(), pattern))
+    }
+
+    /// `column SIMILAR TO pattern`, the SQL standard's regular expressions which
+    /// have to match the whole column.
+    pub fn similar_to(&self, pattern: &str) -> bool {
+        self.record_text(Where::similar_to(self.column_ref(), pattern))
+    }
+
+    fn record_text(&self, clause: Where) -> bool {
+        let clause = if T::NULLABLE {
+            clause.nullable()
+        } else {
+            clause
+        };
 
-        state.clauses.push(w);
-        ret
+        self.state.borrow_mut().record(clause)
     }
 }
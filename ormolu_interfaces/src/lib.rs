@@ -13,5 +13,11 @@ pub use naming::*;
 mod error;
 pub use error::*;
 
+mod pool;
+pub use pool::*;
+
+mod transaction;
+pub use transaction::*;
+
 pub mod data_type;
 pub use data_type::*;
@@ -0,0 +1,144 @@
+use crate::OrmoluError;
+use sqlx::PgPool;
+use sqlx::postgres::PgPoolOptions;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex, PoisonError};
+use std::time::Duration;
+
+/// The database of every model that does not name another one with
+/// `#[gild(database = "...")]`.
+pub const DEFAULT_DATABASE: &str = "default";
+
+/// How the pool of a database connects and how many connections it keeps,
+/// see [`configure_pool`].
+///
+/// ```rust,ignore
+/// configure_pool("reports", PoolConfig {
+///     max_connections: 2,
+///     acquire_timeout: Duration::from_secs(5),
+///     ..Default::default()
+/// })?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    /// Read from `DATABASE_URL`, or `<NAME>_DATABASE_URL` for a named
+    /// database, when not set
+    pub url: Option<String>,
+    pub max_connections: u32,
+    /// Connections kept open even when idle
+    pub min_connections: u32,
+    /// How long a query waits for a free connection before it fails
+    pub acquire_timeout: Duration,
+    /// Idle connections above `min_connections` are closed after this long
+    pub idle_timeout: Option<Duration>,
+    /// Connections are replaced once they are this old
+    pub max_lifetime: Option<Duration>,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            max_connections: 10,
+            min_connections: 0,
+            acquire_timeout: Duration::from_secs(30),
+            idle_timeout: Some(Duration::from_secs(10 * 60)),
+            max_lifetime: Some(Duration::from_secs(30 * 60)),
+        }
+    }
+}
+
+impl PoolConfig {
+    /// Creates the pool without connecting, the first query opens the first
+    /// connection.
+    fn open(self, database: &str) -> Result<PgPool, OrmoluError> {
+        let url = match self.url {
+            Some(url) => url,
+            None => {
+                let variable = url_variable(database);
+                dotenvy::dotenv().ok();
+                std::env::var(&variable).map_err(|_| OrmoluError::MissingDatabaseUrl {
+                    database: database.to_string(),
+                    variable,
+                })?
+            }
+        };
+
+        Ok(PgPoolOptions::new()
+            .max_connections(self.max_connections)
+            .min_connections(self.min_connections)
+            .acquire_timeout(self.acquire_timeout)
+            .idle_timeout(self.idle_timeout)
+            .max_lifetime(self.max_lifetime)
+            .connect_lazy(&url)?)
+    }
+}
+
+enum Entry {
+    /// Configured but not used yet
+    Configured(PoolConfig),
+    Open(PgPool),
+}
+
+/// One pool per database name for the whole process.
+static POOLS: LazyLock<Mutex<HashMap<String, Entry>>> = LazyLock::new(Default::default);
+
+/// Sets how the pool of `database` connects, it has to be called before the
+/// first query on that database.
+///
+/// # Errors
+/// [`OrmoluError::PoolInUse`] when the pool has already been opened.
+pub fn configure_pool(database: &str, config: PoolConfig) -> Result<(), OrmoluError> {
+    set_entry(database, Entry::Configured(config))
+}
+
+/// Uses an existing pool for `database`, for a pool that is shared with code
+/// outside of ormolu.
+///
+/// # Errors
+/// [`OrmoluError::PoolInUse`] when the pool has already been opened.
+pub fn register_pool(database: &str, pool: PgPool) -> Result<(), OrmoluError> {
+    set_entry(database, Entry::Open(pool))
+}
+
+/// The pool of `database`, opened the first time it is asked for with the
+/// [`PoolConfig`] it was configured with.
+///
+/// Connections stay bound to the async runtime that opened them, so the pool
+/// is meant for the one runtime of the application.
+pub fn database_pool(database: &str) -> Result<PgPool, OrmoluError> {
+    let mut pools = POOLS.lock().unwrap_or_else(PoisonError::into_inner);
+
+    let config = match pools.get(database) {
+        Some(Entry::Open(pool)) => return Ok(pool.clone()),
+        Some(Entry::Configured(config)) => config.clone(),
+        None => PoolConfig::default(),
+    };
+
+    let pool = config.open(database)?;
+    pools.insert(database.to_string(), Entry::Open(pool.clone()));
+    Ok(pool)
+}
+
+fn set_entry(database: &str, entry: Entry) -> Result<(), OrmoluError> {
+    let mut pools = POOLS.lock().unwrap_or_else(PoisonError::into_inner);
+
+    if let Some(Entry::Open(_)) = pools.get(database) {
+        return Err(OrmoluError::PoolInUse {
+            database: database.to_string(),
+        });
+    }
+
+    pools.insert(database.to_string(), entry);
+    Ok(())
+}
+
+/// `DATABASE_URL` for the default database, `REPORTS_DATABASE_URL` for one
+/// named `reports`.
+fn url_variable(database: &str) -> String {
+    if database == DEFAULT_DATABASE {
+        return "DATABASE_URL".to_string();
+    }
+
+    format!("{}_DATABASE_URL", database.to_uppercase().replace('-', "_"))
+}
@@ -1,18 +1,32 @@
 // https://www.geeksforgeeks.org/sql-ddl-dql-dml-dcl-tcl-commands/
 pub mod query;
-// use query::*;
+use query::Expr;
+
+mod value;
+pub use value::*;
 
 pub enum Command {
     // -- Data Manipulation
-    Insert {},
-    Update {},
+    Insert {
+        columns: &'static [&'static str],
+        /// One list of values per inserted row, in `columns` order
+        rows: Vec<Vec<SqlValue>>,
+        /// Columns of the inserted rows sent back to us
+        returning: Vec<Expr>,
+    },
+    Update {
+        /// Columns set on every matching row and their new values
+        assignments: Vec<(&'static str, SqlValue)>,
+    },
     Delete,
     // LOCK,
     // CALL,
     // EXPLAIN PLAN,
 
     // -- Data Query
-    Select { columns: &'static [&'static str] },
+    Select {
+        columns: Vec<Expr>,
+    },
     // -- Data Define
     // Create {},
     // DROP,
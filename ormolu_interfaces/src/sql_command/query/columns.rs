@@ -0,0 +1,67 @@
+use super::Expr;
+use crate::{Agg, Col};
+
+/// A proxy field or aggregate that can be picked by a select closure.
+pub trait Selected {
+    /// What the value decodes into
+    type Output;
+
+    fn expr(&self) -> Expr;
+}
+
+impl<T> Selected for Col<T> {
+    type Output = T;
+
+    fn expr(&self) -> Expr {
+        self.column_ref().into()
+    }
+}
+
+impl<T> Selected for Agg<T> {
+    type Output = T;
+
+    fn expr(&self) -> Expr {
+        self.expr.clone()
+    }
+}
+
+/// Columns picked by a select closure, a tuple of proxy fields or aggregates.
+///
+/// `Output` is what one row of the selected columns decodes into:
+/// ```rust,ignore
+/// // QuerySet<Order, (String, Unique<String>)>
+/// let rows = orders
+///     .inner_join::<Customer, _>()
+///     .select_joined::<Customer, _>(|o, c| (o.name, c.email));
+/// ```
+pub trait Columns {
+    type Output;
+
+    fn exprs(&self) -> Vec<Expr>;
+}
+
+macro_rules! impl_columns_for_tuple {
+    ($($col:ident: $ty:ident),+) => {
+        impl<$($ty: Selected),+> Columns for ($($ty,)+) {
+            type Output = ($($ty::Output,)+);
+
+            fn exprs(&self) -> Vec<Expr> {
+                let ($($col,)+) = self;
+                vec![$($col.expr()),+]
+            }
+        }
+    };
+}
+
+impl_columns_for_tuple!(a: A);
+impl_columns_for_tuple!(a: A, b: B);
+impl_columns_for_tuple!(a: A, b: B, c: C);
+impl_columns_for_tuple!(a: A, b: B, c: C, d: D);
+impl_columns_for_tuple!(a: A, b: B, c: C, d: D, e: E);
+impl_columns_for_tuple!(a: A, b: B, c: C, d: D, e: E, f: F);
+impl_columns_for_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G);
+impl_columns_for_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H);
+impl_columns_for_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I);
+impl_columns_for_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J);
+impl_columns_for_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K);
+impl_columns_for_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L);
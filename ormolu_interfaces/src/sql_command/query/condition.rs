@@ -0,0 +1,262 @@
+use super::{SqlWriter, Where};
+use crate::SqlValue;
+use std::ops::{BitAnd, BitOr, Not};
+
+/// A boolean expression of [`Where`] conditions that can be grouped with
+/// AND, OR and NOT.
+///
+/// Similar to Django's `Q` objects, a `Q` can be built up and negated outside of a
+/// filter closure and passed to [`QuerySet::filter_q`](super::QuerySet::filter_q):
+/// ```rust,ignore
+/// let cheap_or_test = Q::from(Where::eq("status", "test")) | Where::like("name", "%sale%");
+/// let orders = orders.filter_q(!cheap_or_test & Where::eq("customer_id", 4));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Q {
+    Where(Where),
+    /// True when every condition is true, an empty `And` is always true
+    And(Vec<Q>),
+    /// True when any condition is true, an empty `Or` is always false
+    Or(Vec<Q>),
+    Not(Box<Q>),
+}
+
+impl Q {
+    /// A condition every row matches.
+    pub const TRUE: Q = Q::And(Vec::new());
+
+    /// A condition no row matches.
+    pub const FALSE: Q = Q::Or(Vec::new());
+
+    /// Joins all conditions with AND.
+    pub fn all(conditions: impl IntoIterator<Item = impl Into<Q>>) -> Self {
+        conditions
+            .into_iter()
+            .fold(Q::And(Vec::new()), |acc, q| acc.and(q))
+    }
+
+    /// Joins all conditions with OR.
+    pub fn any(conditions: impl IntoIterator<Item = impl Into<Q>>) -> Self {
+        conditions
+            .into_iter()
+            .fold(Q::Or(Vec::new()), |acc, q| acc.or(q))
+    }
+
+    pub fn and(self, other: impl Into<Q>) -> Self {
+        let mut conditions = self.into_and_list();
+        conditions.append(&mut other.into().into_and_list());
+        Q::from_and_list(conditions)
+    }
+
+    pub fn or(self, other: impl Into<Q>) -> Self {
+        let mut conditions = self.into_or_list();
+        conditions.append(&mut other.into().into_or_list());
+        Q::from_or_list(conditions)
+    }
+
+    /// Negates the condition, `!!q` is simplified back to `q`.
+    pub fn negate(self) -> Self {
+        match self {
+            Q::Not(inner) => *inner,
+            q => Q::Not(Box::new(q)),
+        }
+    }
+
+    /// The conditions joined by AND at the top of this condition.
+    pub(crate) fn and_list(&self) -> Vec<Q> {
+        match self {
+            Q::And(conditions) => conditions.clone(),
+            q => vec![q.clone()],
+        }
+    }
+
+    /// The conditions joined by OR at the top of this condition.
+    pub(crate) fn or_list(&self) -> Vec<Q> {
+        match self {
+            Q::Or(conditions) => conditions.clone(),
+            q => vec![q.clone()],
+        }
+    }
+
+    fn into_and_list(self) -> Vec<Q> {
+        match self {
+            Q::And(conditions) => conditions,
+            q => vec![q],
+        }
+    }
+
+    fn into_or_list(self) -> Vec<Q> {
+        match self {
+            Q::Or(conditions) => conditions,
+            q => vec![q],
+        }
+    }
+
+    fn from_and_list(mut conditions: Vec<Q>) -> Self {
+        if conditions.len() == 1 {
+            conditions.remove(0)
+        } else {
+            Q::And(conditions)
+        }
+    }
+
+    fn from_or_list(mut conditions: Vec<Q>) -> Self {
+        if conditions.len() == 1 {
+            conditions.remove(0)
+        } else {
+            Q::Or(conditions)
+        }
+    }
+
+    /// Writes the condition, grouping nested AND/OR with parentheses.
+    pub fn write_sql(&self, w: &mut SqlWriter) {
+        match self {
+            Q::Where(cond) => cond.write_sql(w),
+            Q::And(conditions) if conditions.is_empty() => w.push("TRUE"),
+            Q::Or(conditions) if conditions.is_empty() => w.push("FALSE"),
+            Q::And(conditions) => Self::write_joined(w, conditions, " AND "),
+            Q::Or(conditions) => Self::write_joined(w, conditions, " OR "),
+            Q::Not(inner) => match &**inner {
+                Q::Where(cond) => cond.clone().wrap_not().write_sql(w),
+                Q::Not(q) => q.write_sql(w),
+                q => {
+                    w.push("NOT (");
+                    q.write_sql(w);
+                    w.push(")");
+                }
+            },
+        }
+    }
+
+    fn write_joined(w: &mut SqlWriter, conditions: &[Q], separator: &str) {
+        for (i, condition) in conditions.iter().enumerate() {
+            if i > 0 {
+                w.push(separator);
+            }
+
+            if matches!(condition, Q::And(c) | Q::Or(c) if c.len() > 1) {
+                w.push("(");
+                condition.write_sql(w);
+                w.push(")");
+            } else {
+                condition.write_sql(w);
+            }
+        }
+    }
+
+    /// Renders only the condition, mostly useful for debugging.
+    pub fn to_sql(&self) -> (String, Vec<SqlValue>) {
+        let mut w = SqlWriter::default();
+        self.write_sql(&mut w);
+        w.finish()
+    }
+}
+
+impl From<Where> for Q {
+    fn from(cond: Where) -> Self {
+        Q::Where(cond)
+    }
+}
+
+impl<R: Into<Q>> BitAnd<R> for Q {
+    type Output = Q;
+
+    fn bitand(self, rhs: R) -> Self::Output {
+        self.and(rhs)
+    }
+}
+
+impl<R: Into<Q>> BitOr<R> for Q {
+    type Output = Q;
+
+    fn bitor(self, rhs: R) -> Self::Output {
+        self.or(rhs)
+    }
+}
+
+impl Not for Q {
+    type Output = Q;
+
+    fn not(self) -> Self::Output {
+        self.negate()
+    }
+}
+
+impl Where {
+    /// Combines both conditions with AND.
+    pub fn and(self, other: impl Into<Q>) -> Q {
+        Q::from(self).and(other)
+    }
+
+    /// Combines both conditions with OR.
+    pub fn or(self, other: impl Into<Q>) -> Q {
+        Q::from(self).or(other)
+    }
+}
+
+impl Not for Where {
+    type Output = Where;
+
+    fn not(self) -> Self::Output {
+        self.wrap_not()
+    }
+}
+
+#[cfg(test)]
+mod condition_tests {
+    use super::*;
+
+    #[test]
+    fn groups_or_inside_and() {
+        let q = Where::eq("a", 1)
+            .or(Where::eq("b", 2))
+            .and(Where::eq("c", 3));
+        let (sql, values) = q.to_sql();
+
+        assert_eq!(sql, "(\"a\" = $1 OR \"b\" = $2) AND \"c\" = $3");
+        assert_eq!(
+            values,
+            vec![
+                SqlValue::Integer(1),
+                SqlValue::Integer(2),
+                SqlValue::Integer(3)
+            ]
+        );
+    }
+
+    #[test]
+    fn flattens_chained_combinators() {
+        let q = Q::from(Where::eq("a", 1)) & Where::eq("b", 2) & Where::eq("c", 3);
+        assert_eq!(q.to_sql().0, "\"a\" = $1 AND \"b\" = $2 AND \"c\" = $3");
+    }
+
+    #[test]
+    fn negation() {
+        let single = !Q::from(Where::like("name", "%test%"));
+        assert_eq!(single.to_sql().0, "\"name\" NOT LIKE $1");
+
+        let group = !(Q::from(Where::eq("a", 1)) | Where::eq("b", 2));
+        assert_eq!(group.to_sql().0, "NOT (\"a\" = $1 OR \"b\" = $2)");
+
+        assert_eq!((!group).to_sql().0, "\"a\" = $1 OR \"b\" = $2");
+    }
+
+    #[test]
+    fn negated_nullable_comparisons_match_null() {
+        let like = !Q::from(Where::like("phone", "555%").nullable());
+        assert_eq!(
+            like.to_sql().0,
+            "(\"phone\" NOT LIKE $1 OR \"phone\" IS NULL)"
+        );
+
+        let (sql, values) = Q::from(Where::is_null("phone")).to_sql();
+        assert_eq!(sql, "\"phone\" IS NULL");
+        assert!(values.is_empty());
+    }
+
+    #[test]
+    fn empty_groups() {
+        assert_eq!(Q::all(Vec::<Q>::new()).to_sql().0, "TRUE");
+        assert_eq!(Q::any(Vec::<Q>::new()).to_sql().0, "FALSE");
+    }
+}
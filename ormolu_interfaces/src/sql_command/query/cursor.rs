@@ -0,0 +1,144 @@
+use crate::{OrmoluError, SqlValue};
+use futures::future::BoxFuture;
+use futures::stream::{self, Stream};
+use sqlx::{FromRow, Postgres, Transaction, postgres::PgRow};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Rows fetched from a [`Cursor`] at a time unless
+/// [`QuerySet::chunk_size`](super::QuerySet::chunk_size) says otherwise.
+pub const DEFAULT_CHUNK_SIZE: usize = 1000;
+
+/// A transaction holds one cursor at a time, so the name never clashes. It is
+/// closed before its savepoint is released, which leaves it open otherwise.
+const CURSOR_NAME: &str = "ormolu_rows";
+
+/// Rows of a SELECT read through a server-side cursor, `chunk_size` rows at a
+/// time.
+///
+/// The next chunk is only fetched once every row of the previous one has been
+/// polled, so a slow consumer never has more than one chunk in memory. The
+/// cursor lives in its own transaction, a savepoint when the rows are read in
+/// a transaction, which is rolled back and closes it when the stream is
+/// dropped before the last row.
+pub struct Cursor<'c, R> {
+    rows: Pin<Box<dyn Stream<Item = Result<R, OrmoluError>> + Send + 'c>>,
+}
+
+/// Starts the transaction the cursor is declared in.
+pub type BeginTransaction<'c> = BoxFuture<'c, Result<Transaction<'c, Postgres>, OrmoluError>>;
+
+enum State<'c, R> {
+    /// Nothing was sent to the database yet
+    Declare {
+        begin: BeginTransaction<'c>,
+        sql: String,
+        values: Vec<SqlValue>,
+    },
+    Open {
+        tx: Box<Transaction<'c, Postgres>>,
+        rows: std::vec::IntoIter<R>,
+        /// The last FETCH returned fewer rows than asked for
+        exhausted: bool,
+    },
+    Done,
+}
+
+impl<'c, R> Cursor<'c, R>
+where
+    R: for<'r> FromRow<'r, PgRow> + Send + Unpin + 'c,
+{
+    /// Declares the cursor in the transaction `begin` starts on the first poll,
+    /// `sql` is the rendered SELECT.
+    pub fn new(
+        begin: BeginTransaction<'c>,
+        sql: String,
+        values: Vec<SqlValue>,
+        chunk_size: usize,
+    ) -> Self {
+        let chunk_size = chunk_size.max(1);
+        let fetch = format!("FETCH FORWARD {chunk_size} FROM {CURSOR_NAME}");
+        let state = State::Declare { begin, sql, values };
+
+        let rows = stream::unfold((state, fetch), move |(state, fetch)| async move {
+            match Self::next_row(state, &fetch, chunk_size).await {
+                Ok(Some((row, state))) => Some((Ok(row), (state, fetch))),
+                Ok(None) => None,
+                Err(error) => Some((Err(error), (State::Done, fetch))),
+            }
+        });
+
+        Self {
+            rows: Box::pin(rows),
+        }
+    }
+
+    async fn next_row(
+        mut state: State<'c, R>,
+        fetch: &str,
+        chunk_size: usize,
+    ) -> Result<Option<(R, State<'c, R>)>, OrmoluError> {
+        loop {
+            state = match state {
+                State::Declare { begin, sql, values } => {
+                    let mut tx = Box::new(begin.await?);
+
+                    let declare = format!(
+                        "DECLARE {CURSOR_NAME} NO SCROLL CURSOR FOR {}",
+                        sql.trim_end_matches(';')
+                    );
+                    sqlx::query_with(&declare, SqlValue::into_arguments(values))
+                        .execute(&mut **tx)
+                        .await?;
+
+                    State::Open {
+                        tx,
+                        rows: Vec::new().into_iter(),
+                        exhausted: false,
+                    }
+                }
+                State::Open {
+                    mut tx,
+                    mut rows,
+                    exhausted,
+                } => {
+                    if let Some(row) = rows.next() {
+                        return Ok(Some((
+                            row,
+                            State::Open {
+                                tx,
+                                rows,
+                                exhausted,
+                            },
+                        )));
+                    }
+
+                    if exhausted {
+                        sqlx::query(&format!("CLOSE {CURSOR_NAME}"))
+                            .execute(&mut **tx)
+                            .await?;
+                        tx.commit().await?;
+                        return Ok(None);
+                    }
+
+                    let chunk = sqlx::query_as::<_, R>(fetch).fetch_all(&mut **tx).await?;
+
+                    State::Open {
+                        tx,
+                        exhausted: chunk.len() < chunk_size,
+                        rows: chunk.into_iter(),
+                    }
+                }
+                State::Done => return Ok(None),
+            };
+        }
+    }
+}
+
+impl<R> Stream for Cursor<'_, R> {
+    type Item = Result<R, OrmoluError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rows.as_mut().poll_next(cx)
+    }
+}
@@ -0,0 +1,118 @@
+use super::{ColRef, SqlWriter};
+use crate::SqlValue;
+
+/// An aggregate function computed over the rows of a group.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aggregate {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+/// An arithmetic operator between two expressions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArithOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+/// Something that produces a value for every row, used on the left side of a
+/// [`Where`](super::Where) condition and in SELECT and GROUP BY lists.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Column(ColRef),
+    Aggregate(Aggregate, Box<Expr>),
+    /// `expr::type`, used where Postgres picks a result type we cannot decode
+    Cast(Box<Expr>, &'static str),
+    /// Sent as a bind parameter, never written into the statement text
+    Value(SqlValue),
+    Binary(Box<Expr>, ArithOp, Box<Expr>),
+    /// A placeholder for a value bound on every run of a prepared statement,
+    /// see [`Slot`](super::Slot)
+    Slot(usize),
+    /// `*`, every column, as in `count(*)`
+    Star,
+}
+
+impl Expr {
+    pub fn aggregate(function: Aggregate, expr: impl Into<Expr>) -> Self {
+        Expr::Aggregate(function, Box::new(expr.into()))
+    }
+
+    pub fn cast(self, sql_type: &'static str) -> Self {
+        Expr::Cast(Box::new(self), sql_type)
+    }
+
+    pub fn binary(lhs: impl Into<Expr>, op: ArithOp, rhs: impl Into<Expr>) -> Self {
+        Expr::Binary(Box::new(lhs.into()), op, Box::new(rhs.into()))
+    }
+
+    pub fn write_sql(&self, w: &mut SqlWriter) {
+        match self {
+            Expr::Column(column) => column.write_sql(w),
+            Expr::Aggregate(function, expr) => {
+                w.push(match function {
+                    Aggregate::Count => "count(",
+                    Aggregate::Sum => "sum(",
+                    Aggregate::Avg => "avg(",
+                    Aggregate::Min => "min(",
+                    Aggregate::Max => "max(",
+                });
+                expr.write_sql(w);
+                w.push(")");
+            }
+            Expr::Cast(expr, sql_type) => {
+                expr.write_sql(w);
+                w.push("::");
+                w.push(sql_type);
+            }
+            Expr::Value(value) => w.push_value(value.clone()),
+            Expr::Slot(index) => w.push_slot(*index),
+            Expr::Star => w.push("*"),
+            Expr::Binary(lhs, op, rhs) => {
+                lhs.write_operand(w);
+                w.push(match op {
+                    ArithOp::Add => " + ",
+                    ArithOp::Sub => " - ",
+                    ArithOp::Mul => " * ",
+                    ArithOp::Div => " / ",
+                });
+                rhs.write_operand(w);
+            }
+        }
+    }
+
+    /// Writes the expression as one side of an arithmetic operator, nested
+    /// operations are parenthesized so they keep their grouping.
+    fn write_operand(&self, w: &mut SqlWriter) {
+        if let Expr::Binary(..) = self {
+            w.push("(");
+            self.write_sql(w);
+            w.push(")");
+        } else {
+            self.write_sql(w);
+        }
+    }
+}
+
+impl From<ColRef> for Expr {
+    fn from(column: ColRef) -> Self {
+        Expr::Column(column)
+    }
+}
+
+impl From<SqlValue> for Expr {
+    fn from(value: SqlValue) -> Self {
+        Expr::Value(value)
+    }
+}
+
+impl From<&str> for Expr {
+    fn from(column: &str) -> Self {
+        Expr::Column(ColRef::bare(column))
+    }
+}
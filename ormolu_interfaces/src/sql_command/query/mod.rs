@@ -4,5 +4,26 @@ pub use query_builder::*;
 pub mod where_cond;
 pub use where_cond::*;
 
+mod columns;
+pub use columns::*;
+
+mod condition;
+pub use condition::*;
+
+mod cursor;
+pub use cursor::*;
+
+mod expr;
+pub use expr::*;
+
+mod prepared;
+pub use prepared::*;
+
 mod query_set;
 pub use query_set::*;
+
+mod sql_writer;
+pub use sql_writer::*;
+
+mod subquery;
+pub use subquery::*;
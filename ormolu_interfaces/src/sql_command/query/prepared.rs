@@ -0,0 +1,145 @@
+use super::{Expr, QuerySet};
+use crate::{OrmoluError, PgAcquire, SqlValue, Table, ToExpr, ToNullableExpr};
+use sqlx::{FromRow, postgres::PgRow};
+use std::marker::PhantomData;
+use std::sync::OnceLock;
+
+/// A value compared in a `query!` filter, it is left out of the statement and
+/// bound on every run instead.
+///
+/// `R` is the type of the value so the comparison is still type checked against
+/// the column, the value itself is never read.
+pub struct Slot<R> {
+    index: usize,
+    _value: PhantomData<fn() -> R>,
+}
+
+impl<R> Slot<R> {
+    pub fn of(index: usize, _value: &R) -> Self {
+        Self {
+            index,
+            _value: PhantomData,
+        }
+    }
+}
+
+impl<V, R: ToExpr<V>> ToExpr<V> for Slot<R> {
+    fn to_expr(&self) -> Expr {
+        Expr::Slot(self.index)
+    }
+}
+
+impl<V, R: ToExpr<V>> ToNullableExpr<V> for Slot<R> {
+    fn to_nullable_expr(&self) -> Option<Expr> {
+        Some(self.to_expr())
+    }
+}
+
+/// A bind parameter of a [`PreparedStatement`].
+#[derive(Debug, Clone, PartialEq)]
+enum Param {
+    /// The same on every run, a literal in the filter
+    Value(SqlValue),
+    Slot(usize),
+}
+
+/// A statement rendered once, with the values of its [`Slot`]s bound on every
+/// run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreparedStatement {
+    sql: String,
+    params: Vec<Param>,
+}
+
+impl PreparedStatement {
+    /// `slots` are the `(value index, slot)` pairs of the placeholders written
+    /// for a [`Slot`].
+    pub(crate) fn new(sql: String, values: Vec<SqlValue>, slots: Vec<(usize, usize)>) -> Self {
+        let mut params: Vec<_> = values.into_iter().map(Param::Value).collect();
+        for (index, slot) in slots {
+            params[index] = Param::Slot(slot);
+        }
+
+        Self { sql, params }
+    }
+
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// The values of the placeholders in order, `slots` holds the value of
+    /// every [`Slot`] by index.
+    ///
+    /// # Panics
+    /// When a slot of the statement has no value.
+    pub fn bind(&self, slots: &[SqlValue]) -> Vec<SqlValue> {
+        self.params
+            .iter()
+            .map(|param| match param {
+                Param::Value(value) => value.clone(),
+                Param::Slot(index) => slots[*index].clone(),
+            })
+            .collect()
+    }
+}
+
+/// A statement built by `query!`, rendered the first time it runs and reused
+/// after that. Rows are decoded into `R` like they are for a [`QuerySet`].
+pub struct StaticQuery<T: Table, R = T> {
+    sql: &'static str,
+    values: Vec<SqlValue>,
+    _row: PhantomData<fn() -> (T, R)>,
+}
+
+impl<T: Table, R> StaticQuery<T, R> {
+    /// Prepares the statement of `build` into `statement` unless that was done
+    /// before, then binds `slots` to it.
+    ///
+    /// The SQL is rendered here at runtime, by the first call for a
+    /// `statement`, `query!` only checks the chain during expansion.
+    pub fn new(
+        statement: &'static OnceLock<PreparedStatement>,
+        build: impl FnOnce() -> QuerySet<T, R>,
+        slots: Vec<SqlValue>,
+    ) -> Self {
+        let statement = statement.get_or_init(|| build().prepare());
+
+        Self {
+            sql: statement.sql(),
+            values: statement.bind(&slots),
+            _row: PhantomData,
+        }
+    }
+
+    /// The statement with `$n` placeholders, the same on every run.
+    pub fn sql(&self) -> &'static str {
+        self.sql
+    }
+
+    /// The values bound to the placeholders on this run, in order.
+    pub fn values(&self) -> &[SqlValue] {
+        &self.values
+    }
+}
+
+impl<T: Table, R> StaticQuery<T, R>
+where
+    R: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+{
+    /// Executes the query and returns every row.
+    pub fn all<'c>(
+        self,
+        db: impl PgAcquire<'c>,
+    ) -> impl Future<Output = Result<Vec<R>, OrmoluError>> + Send {
+        let sql = self.sql;
+        let arguments = SqlValue::into_arguments(self.values);
+
+        async move {
+            let mut conn = db.acquire().await?;
+
+            Ok(sqlx::query_as_with::<_, R, _>(sql, arguments)
+                .fetch_all(&mut *conn)
+                .await?)
+        }
+    }
+}
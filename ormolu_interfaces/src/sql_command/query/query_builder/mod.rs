@@ -1,5 +1,6 @@
-use super::Where;
-use crate::{Command, Table};
+use super::{Aggregate, Expr, PreparedStatement, Q, SqlWriter};
+use crate::{Command, Insertable, OrmoluError, PgAcquire, SqlValue, Table};
+use sqlx::postgres::PgArguments;
 use std::marker::PhantomData;
 
 // Ref is an implementation detail you can ignore
@@ -14,6 +15,7 @@ struct Col<T, E, const C: usize> {
     _ref: Ref<E, C>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum JoinType {
     Inner,
     Left,
@@ -22,73 +24,234 @@ pub enum JoinType {
     Cross,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct ColRef {
     schema: Option<String>,
-    object: String,
+    /// `None` when only the column name is known
+    object: Option<String>,
     column: String,
 }
 
+impl ColRef {
+    /// Creates a reference to `column` on a `schema.object` or `object` name.
+    pub fn new(qualified_name: &str, column: &str) -> Self {
+        let (schema, object) = match qualified_name.split_once('.') {
+            Some((schema, object)) => (Some(schema.to_string()), object.to_string()),
+            None => (None, qualified_name.to_string()),
+        };
+
+        Self {
+            schema,
+            object: Some(object),
+            column: column.to_string(),
+        }
+    }
+
+    /// Creates a reference to a column without knowing its table, it is never
+    /// qualified.
+    pub fn bare(column: &str) -> Self {
+        Self {
+            schema: None,
+            object: None,
+            column: column.to_string(),
+        }
+    }
+
+    /// The `schema.object` name of the table the column is on.
+    pub fn table(&self) -> Option<String> {
+        let object = self.object.as_ref()?;
+        Some(match &self.schema {
+            Some(schema) => format!("{schema}.{object}"),
+            None => object.clone(),
+        })
+    }
+
+    /// Writes the quoted column, prefixed with its table when the writer
+    /// qualifies columns.
+    pub fn write_sql(&self, w: &mut SqlWriter) {
+        if w.qualifies_columns()
+            && let Some(object) = &self.object
+        {
+            if let Some(schema) = &self.schema {
+                w.push_ident(schema);
+                w.push(".");
+            }
+            w.push_ident(object);
+            w.push(".");
+        }
+        w.push_ident(&self.column);
+    }
+}
+
+impl From<&str> for ColRef {
+    fn from(column: &str) -> Self {
+        Self::bare(column)
+    }
+}
+
 pub struct Join {
     join_type: JoinType,
-    on: [ColRef; 2],
+    /// Qualified `schema.object` name of the joined table
+    table: String,
+    /// Columns that have to be equal, `None` for a CROSS JOIN
+    on: Option<[ColRef; 2]>,
 }
 
 pub struct GroupBy {
-    columns: Vec<ColRef>,
+    columns: Vec<Expr>,
+}
+
+impl GroupBy {
+    pub fn new(columns: Vec<Expr>) -> Self {
+        Self { columns }
+    }
+
+    fn write_sql(&self, w: &mut SqlWriter) {
+        w.push(" GROUP BY ");
+        write_expr_list(w, &self.columns);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+/// Where NULL values end up, Postgres puts them last for ASC and first for DESC
+/// unless told otherwise.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NullsOrder {
+    First,
+    Last,
+}
+
+pub struct OrderByTerm {
+    pub column: ColRef,
+    pub direction: SortDirection,
+    pub nulls: Option<NullsOrder>,
 }
 
 pub struct OrderBy {
-    columns: Vec<ColRef>,
+    columns: Vec<OrderByTerm>,
 }
 
-impl Join {
-    fn inner(dbo1: ColRef, dbo2: ColRef) -> Self {
+impl OrderBy {
+    pub fn new(term: OrderByTerm) -> Self {
         Self {
-            join_type: JoinType::Inner,
-            on: [dbo1, dbo2],
+            columns: vec![term],
         }
     }
 
-    fn left(dbo1: ColRef, dbo2: ColRef) -> Self {
-        Self {
-            join_type: JoinType::Left,
-            on: [dbo1, dbo2],
+    /// Adds a column used to order rows that are equal on the previous ones.
+    pub fn then(&mut self, term: OrderByTerm) {
+        self.columns.push(term);
+    }
+
+    /// The most recently added column.
+    pub fn last_mut(&mut self) -> Option<&mut OrderByTerm> {
+        self.columns.last_mut()
+    }
+
+    fn write_sql(&self, w: &mut SqlWriter) {
+        w.push(" ORDER BY ");
+        for (i, term) in self.columns.iter().enumerate() {
+            if i > 0 {
+                w.push(", ");
+            }
+
+            term.column.write_sql(w);
+            w.push(match term.direction {
+                SortDirection::Asc => " ASC",
+                SortDirection::Desc => " DESC",
+            });
+
+            match term.nulls {
+                Some(NullsOrder::First) => w.push(" NULLS FIRST"),
+                Some(NullsOrder::Last) => w.push(" NULLS LAST"),
+                None => {}
+            }
         }
     }
+}
+
+impl Join {
+    /// Joins the table of `dbo2` on `dbo1 = dbo2`.
+    pub fn inner(dbo1: ColRef, dbo2: ColRef) -> Self {
+        Self::on(JoinType::Inner, dbo1, dbo2)
+    }
 
-    fn right(dbo1: ColRef, dbo2: ColRef) -> Self {
+    pub fn left(dbo1: ColRef, dbo2: ColRef) -> Self {
+        Self::on(JoinType::Left, dbo1, dbo2)
+    }
+
+    pub fn right(dbo1: ColRef, dbo2: ColRef) -> Self {
+        Self::on(JoinType::Right, dbo1, dbo2)
+    }
+
+    pub fn full(dbo1: ColRef, dbo2: ColRef) -> Self {
+        Self::on(JoinType::Full, dbo1, dbo2)
+    }
+
+    /// Pairs every row with every row of `table`, there is nothing to join on.
+    pub fn cross(table: &str) -> Self {
         Self {
-            join_type: JoinType::Right,
-            on: [dbo1, dbo2],
+            join_type: JoinType::Cross,
+            table: table.to_string(),
+            on: None,
         }
     }
 
-    fn full(dbo1: ColRef, dbo2: ColRef) -> Self {
+    /// Joins the table of `dbo2` on `dbo1 = dbo2`, a CROSS JOIN ignores the columns.
+    ///
+    /// # Panics
+    /// When `dbo2` was created with [`ColRef::bare`] and has no table.
+    pub fn on(join_type: JoinType, dbo1: ColRef, dbo2: ColRef) -> Self {
+        let table = dbo2
+            .table()
+            .expect("the joined column has to reference its table");
+
+        if join_type == JoinType::Cross {
+            return Self::cross(&table);
+        }
+
         Self {
-            join_type: JoinType::Full,
-            on: [dbo1, dbo2],
+            join_type,
+            table,
+            on: Some([dbo1, dbo2]),
         }
     }
 
-    fn cross(dbo1: ColRef, dbo2: ColRef) -> Self {
-        Self {
-            join_type: JoinType::Cross,
-            on: [dbo1, dbo2],
+    fn write_sql(&self, w: &mut SqlWriter) {
+        w.push(match self.join_type {
+            JoinType::Inner => " INNER JOIN ",
+            JoinType::Left => " LEFT JOIN ",
+            JoinType::Right => " RIGHT JOIN ",
+            JoinType::Full => " FULL JOIN ",
+            JoinType::Cross => " CROSS JOIN ",
+        });
+        w.push_qualified_ident(&self.table);
+
+        if let Some([lhs, rhs]) = &self.on {
+            w.push(" ON ");
+            lhs.write_sql(w);
+            w.push(" = ");
+            rhs.write_sql(w);
         }
     }
 }
 
-/// The HAVING clause was added to SQL because the WHERE keyword cannot be used with aggregate functions.
-pub struct Having {}
-
 pub struct QueryState<T> {
     pub command: Command,
+    /// Qualified `schema.object` name of the table
     pub table: &'static str,
-    pub where_conditions: Vec<Where>,
+    /// Everything that ends up in the WHERE clause, `None` matches every row
+    pub where_clause: Option<Q>,
     pub joins: Vec<Join>,
     pub order_by: Option<OrderBy>,
     pub group_by: Option<GroupBy>,
-    pub having: Option<Having>,
+    /// The HAVING clause was added to SQL because the WHERE keyword cannot be used with aggregate functions.
+    pub having: Option<Q>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
     _table: PhantomData<T>,
@@ -96,12 +259,85 @@ pub struct QueryState<T> {
 
 impl<T: Table> QueryState<T> {
     pub fn new_select() -> Self {
+        Self::new(Command::Select {
+            columns: Self::all_columns(),
+        })
+    }
+
+    /// An INSERT of the given rows that returns them as stored.
+    pub fn new_insert(rows: Vec<Vec<SqlValue>>) -> Self
+    where
+        T: Insertable,
+    {
+        Self::new(Command::Insert {
+            columns: T::insert_columns(),
+            rows,
+            returning: Self::all_columns(),
+        })
+    }
+
+    /// An UPDATE that sets the columns on every row matching the WHERE clause.
+    pub fn new_update(assignments: Vec<(&'static str, SqlValue)>) -> Self {
+        Self::new(Command::Update { assignments })
+    }
+
+    /// A DELETE of every row matching the WHERE clause.
+    pub fn new_delete() -> Self {
+        Self::new(Command::Delete)
+    }
+
+    /// Runs a statement that returns no rows and returns the number of rows it
+    /// affected.
+    ///
+    /// An UPDATE that sets no columns is not sent and affects no rows.
+    pub fn execute<'c, A: PgAcquire<'c>>(
+        &self,
+        db: A,
+    ) -> impl Future<Output = Result<u64, OrmoluError>> + Send + use<'c, A, T> {
+        let statement = match &self.command {
+            Command::Update { assignments } if assignments.is_empty() => None,
+            _ => Some(self.build()),
+        };
+
+        async move {
+            let Some((sql, arguments)) = statement else {
+                return Ok(0);
+            };
+            let mut conn = db.acquire().await?;
+
+            Ok(sqlx::query_with(sql.as_str(), arguments)
+                .execute(&mut *conn)
+                .await?
+                .rows_affected())
+        }
+    }
+
+    fn new(command: Command) -> Self {
+        Self::on_table(T::qualified_name(), command)
+    }
+
+    fn all_columns() -> Vec<Expr> {
+        T::database_columns()
+            .iter()
+            .map(|column| ColRef::new(T::qualified_name(), column).into())
+            .collect()
+    }
+}
+
+impl QueryState<()> {
+    /// A SELECT on a table only known by name, for statements nested in
+    /// another one.
+    pub(crate) fn new_select_from(table: &'static str, columns: Vec<Expr>) -> Self {
+        Self::on_table(table, Command::Select { columns })
+    }
+}
+
+impl<T> QueryState<T> {
+    fn on_table(table: &'static str, command: Command) -> Self {
         Self {
-            command: Command::Select {
-                columns: T::database_columns(),
-            },
-            table: T::object_name(),
-            where_conditions: Vec::new(),
+            command,
+            table,
+            where_clause: None,
             joins: Vec::new(),
             limit: None,
             offset: None,
@@ -111,45 +347,229 @@ impl<T: Table> QueryState<T> {
             having: None,
         }
     }
-}
 
-impl<T> std::fmt::Display for QueryState<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let command = match &self.command {
+    /// Adds a join, columns are written with their table once the statement has one.
+    pub fn join(&mut self, join: Join) {
+        self.joins.push(join);
+    }
+
+    /// Adds a condition that every row has to match as well.
+    pub fn and_where(&mut self, condition: impl Into<Q>) {
+        self.where_clause = Some(match self.where_clause.take() {
+            Some(existing) => existing.and(condition),
+            None => condition.into(),
+        });
+    }
+
+    /// Adds a condition that every group has to match as well.
+    pub fn and_having(&mut self, condition: impl Into<Q>) {
+        self.having = Some(match self.having.take() {
+            Some(existing) => existing.and(condition),
+            None => condition.into(),
+        });
+    }
+
+    /// Adds a condition that rows can match instead of the existing ones.
+    ///
+    /// Without existing conditions every row already matches so this is a no-op.
+    pub fn or_where(&mut self, condition: impl Into<Q>) {
+        if let Some(existing) = self.where_clause.take() {
+            self.where_clause = Some(existing.or(condition));
+        }
+    }
+
+    /// Renders the statement with `$n` placeholders and returns it alongside
+    /// the values that have to be bound to them, in order.
+    pub fn to_sql(&self) -> (String, Vec<SqlValue>) {
+        let mut w = SqlWriter::default();
+        // with joins a bare column name could belong to more than one table
+        self.write_sql(&mut w, !self.joins.is_empty());
+        w.push(";");
+        w.finish()
+    }
+
+    /// Renders the statement once so it can be run again with other values
+    /// in its [`Slot`](super::Slot)s.
+    pub fn prepare(&self) -> PreparedStatement {
+        let mut w = SqlWriter::default();
+        self.write_sql(&mut w, !self.joins.is_empty());
+        w.push(";");
+
+        let (sql, values, slots) = w.finish_with_slots();
+        PreparedStatement::new(sql, values, slots)
+    }
+
+    /// Writes the statement without the trailing `;`, so it can be nested in
+    /// another one.
+    pub fn write_sql(&self, w: &mut SqlWriter, qualify_columns: bool) {
+        w.qualify_columns(qualify_columns);
+
+        match &self.command {
             Command::Select { columns } => {
-                format!("SELECT {} FROM {}", columns.join(", "), self.table)
+                w.push("SELECT ");
+                write_expr_list(w, columns);
+                w.push(" FROM ");
+                w.push_qualified_ident(self.table);
+
+                for join in &self.joins {
+                    join.write_sql(w);
+                }
+            }
+            Command::Insert {
+                columns,
+                rows,
+                returning,
+            } => {
+                w.push("INSERT INTO ");
+                w.push_qualified_ident(self.table);
+
+                if columns.is_empty() {
+                    w.push(" DEFAULT VALUES");
+                } else {
+                    w.push(" (");
+                    w.push_ident_list(columns);
+                    w.push(") VALUES ");
+
+                    for (i, row) in rows.iter().enumerate() {
+                        if i > 0 {
+                            w.push(", ");
+                        }
+                        w.push("(");
+                        for (j, value) in row.iter().enumerate() {
+                            if j > 0 {
+                                w.push(", ");
+                            }
+                            w.push_value(value.clone());
+                        }
+                        w.push(")");
+                    }
+                }
+
+                w.push(" RETURNING ");
+                write_expr_list(w, returning);
+            }
+            Command::Update { assignments } => {
+                w.push("UPDATE ");
+                w.push_qualified_ident(self.table);
+                w.push(" SET ");
+
+                for (i, (column, value)) in assignments.iter().enumerate() {
+                    if i > 0 {
+                        w.push(", ");
+                    }
+                    w.push_ident(column);
+                    w.push(" = ");
+                    w.push_value(value.clone());
+                }
             }
             Command::Delete => {
-                format!("DELETE FROM {}", self.table)
+                w.push("DELETE FROM ");
+                w.push_qualified_ident(self.table);
             }
-            _ => todo!(),
         };
 
-        let where_conditions = if self.where_conditions.is_empty() {
-            String::new()
-        } else {
-            let where_conditions = self
-                .where_conditions
-                .iter()
-                .map(|c| c.to_string())
-                .collect::<Vec<String>>()
-                .join(" AND ");
-
-            format!(" WHERE {where_conditions}")
-        };
+        if let Some(condition) = &self.where_clause {
+            w.push(" WHERE ");
+            condition.write_sql(w);
+        }
 
-        let limit = if let Some(lmt) = self.limit {
-            format!(" LIMIT {lmt}")
-        } else {
-            String::new()
-        };
+        if let Some(group_by) = &self.group_by {
+            group_by.write_sql(w);
+        }
+
+        if let Some(condition) = &self.having {
+            w.push(" HAVING ");
+            condition.write_sql(w);
+        }
+
+        if let Some(order_by) = &self.order_by {
+            order_by.write_sql(w);
+        }
+
+        if let Some(lmt) = self.limit {
+            w.push(&format!(" LIMIT {lmt}"));
+        }
 
-        let offset = if let Some(oft) = self.offset {
-            format!(" OFFSET {oft}")
-        } else {
-            String::new()
+        if let Some(oft) = self.offset {
+            w.push(&format!(" OFFSET {oft}"));
+        }
+    }
+
+    /// Rewrites a SELECT into one returning the number of rows it matches,
+    /// rendered like [`QueryState::to_sql`].
+    ///
+    /// Ordering is dropped since it does not change the count. A grouped or
+    /// limited SELECT is counted as a subquery, its rows are not the rows of
+    /// the table.
+    pub fn into_count_sql(mut self) -> (String, Vec<SqlValue>) {
+        let limited = self.limit.is_some() || self.offset.is_some();
+        if !limited {
+            self.order_by = None;
+        }
+
+        if limited || self.group_by.is_some() {
+            let mut w = SqlWriter::default();
+            w.push("SELECT count(*) FROM (");
+            self.write_sql(&mut w, !self.joins.is_empty());
+            w.push(") AS \"rows\";");
+            return w.finish();
+        }
+
+        self.command = Command::Select {
+            columns: vec![Expr::aggregate(Aggregate::Count, Expr::Star)],
         };
+        self.to_sql()
+    }
 
-        write!(f, "{command}{where_conditions}{limit}{offset};")
+    /// Rewrites a SELECT into `SELECT EXISTS (...)`, which stops at the first
+    /// matching row.
+    pub fn into_exists_sql(mut self) -> (String, Vec<SqlValue>) {
+        if self.limit.is_none() && self.offset.is_none() {
+            self.order_by = None;
+        }
+
+        let mut w = SqlWriter::default();
+        w.push("SELECT EXISTS (");
+        self.write_sql(&mut w, !self.joins.is_empty());
+        w.push(");");
+        w.finish()
+    }
+
+    /// Forgets the table type, for statements nested in a statement on
+    /// another table.
+    pub(crate) fn erase(self) -> QueryState<()> {
+        QueryState {
+            command: self.command,
+            table: self.table,
+            where_clause: self.where_clause,
+            joins: self.joins,
+            order_by: self.order_by,
+            group_by: self.group_by,
+            having: self.having,
+            limit: self.limit,
+            offset: self.offset,
+            _table: PhantomData,
+        }
+    }
+
+    /// Renders the statement and converts its values into sqlx arguments.
+    pub fn build(&self) -> (String, PgArguments) {
+        let (sql, values) = self.to_sql();
+        (sql, SqlValue::into_arguments(values))
+    }
+}
+
+fn write_expr_list(w: &mut SqlWriter, exprs: &[Expr]) {
+    for (i, expr) in exprs.iter().enumerate() {
+        if i > 0 {
+            w.push(", ");
+        }
+        expr.write_sql(w);
+    }
+}
+
+impl<T> std::fmt::Display for QueryState<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_sql().0)
     }
 }
@@ -1,7 +1,15 @@
 pub use super::QueryState;
+use super::{
+    ColRef, Columns, Cursor, DEFAULT_CHUNK_SIZE, GroupBy, Join, JoinType, NullsOrder, OrderBy,
+    OrderByTerm, PreparedStatement, Q, SortDirection, Subquery, Where,
+};
 use crate::*;
 use futures::stream::Stream;
+use sqlx::{FromRow, postgres::PgRow};
+use std::cell::RefCell;
+use std::marker::PhantomData;
 use std::pin::*;
+use std::rc::Rc;
 use std::task::*;
 
 /**
@@ -13,7 +21,7 @@ You can evaluate a QuerySet in the following ways:
 
 - Iteration. A QuerySet is iterable, and it executes its database query the first time you iterate over it. For example, this will print the headline of all entries in the database:
 ```rust,ignore
-    let customer = Customer::get_by_id(1).await?;
+    let customer = Customer::get_by_id(&pool, 1).await?;
     let orders: QuerySet<Order> = customer::orders();
     let real_orders = orders.filter(|o| !o.test);
 
@@ -22,25 +30,202 @@ You can evaluate a QuerySet in the following ways:
     }
 ```
 
-- Asynchronous iteration. A QuerySet can also be iterated over using async for:
+- Asynchronous iteration. A QuerySet is also a [`Stream`] of rows, which are
+  read through a server-side cursor a chunk at a time, see [`Cursor`]:
 ```rust,ignore
     while let Some(order) = orders.next().await {
-        println!("ORDER: {}", order);
+        println!("ORDER: {}", order?);
     }
 ```
+
+- Terminal methods. [`QuerySet::all`], [`QuerySet::first`], [`QuerySet::one`]
+  and [`QuerySet::get`] return rows, [`QuerySet::count`] and
+  [`QuerySet::exists`] only ask the database about them:
+```rust,ignore
+    if !orders.filter(|o| o.status == "open").exists(&pool).await? {
+        println!("nothing to ship");
+    }
+```
+
+Rows are decoded into `R`, which is the table itself unless the selected
+columns were changed, see [`QuerySet::select_joined`].
 */
-pub struct QuerySet<T: Table> {
+pub struct QuerySet<T: Table, R = T> {
     // TODO: merge QueryState into queryset
     state: QueryState<T>,
+    /// Whether an UPDATE or DELETE may run without a filter
+    all_rows: bool,
+    /// Rows fetched at a time when streaming
+    chunk_size: usize,
+    /// Opened on the first poll of the stream
+    cursor: Option<Cursor<'static, R>>,
+    _row: PhantomData<fn() -> R>,
 }
 
 impl<T: Table> QuerySet<T> {
     pub fn new(state: QueryState<T>) -> Self {
-        Self { state }
+        Self {
+            state,
+            all_rows: false,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            cursor: None,
+            _row: PhantomData,
+        }
+    }
+}
+
+impl<T: Table, R> QuerySet<T, R> {
+    /// Renders the underlying statement, see [`QueryState::to_sql`].
+    pub fn to_sql(&self) -> (String, Vec<SqlValue>) {
+        self.state.to_sql()
+    }
+
+    /// Renders the underlying statement for reuse, see [`QueryState::prepare`].
+    pub fn prepare(&self) -> PreparedStatement {
+        self.state.prepare()
     }
 }
 
-impl<T: Table> std::fmt::Display for QuerySet<T> {
+impl<T: Table, R> QuerySet<T, R>
+where
+    R: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+{
+    /// Executes the query and returns every row.
+    ///
+    /// ```rust,ignore
+    /// let orders = customer.orders().all(&pool).await?;
+    /// ```
+    pub fn all<'c>(
+        self,
+        db: impl PgAcquire<'c>,
+    ) -> impl Future<Output = Result<Vec<R>, OrmoluError>> + Send {
+        let (sql, arguments) = self.state.build();
+
+        async move {
+            let mut conn = db.acquire().await?;
+
+            Ok(sqlx::query_as_with::<_, R, _>(sql.as_str(), arguments)
+                .fetch_all(&mut *conn)
+                .await?)
+        }
+    }
+
+    /// Executes the query with `LIMIT 1` and returns the first row, if any.
+    ///
+    /// ```rust,ignore
+    /// let newest = orders.order_by_desc(|o| o.created_at).first(&pool).await?;
+    /// ```
+    pub fn first<'c>(
+        self,
+        db: impl PgAcquire<'c>,
+    ) -> impl Future<Output = Result<Option<R>, OrmoluError>> + Send {
+        let rows = self.limit_to(1).all(db);
+        async move { Ok(rows.await?.pop()) }
+    }
+
+    /// Executes the query and returns its only row.
+    ///
+    /// At most two rows are fetched, that is enough to tell there is more
+    /// than one. They are taken from the rows the query returns, after its own
+    /// limit and offset, so `orders.limit(1).one(&pool)` is never
+    /// [`OrmoluError::MultipleRows`] however many orders there are.
+    ///
+    /// # Errors
+    /// [`OrmoluError::NotFound`] when no row matches and
+    /// [`OrmoluError::MultipleRows`] when more than one does.
+    pub fn one<'c>(
+        self,
+        db: impl PgAcquire<'c>,
+    ) -> impl Future<Output = Result<R, OrmoluError>> + Send {
+        let table = self.state.table;
+        let rows = self.limit_to(2).all(db);
+
+        async move {
+            let mut rows = rows.await?;
+
+            match rows.len() {
+                0 => Err(OrmoluError::NotFound { table }),
+                1 => Ok(rows.remove(0)),
+                _ => Err(OrmoluError::MultipleRows { table }),
+            }
+        }
+    }
+
+    /// Returns the row with this primary key, if it matches the filters.
+    ///
+    /// ```rust,ignore
+    /// let order = customer.orders().get(&pool, 42).await?;
+    /// ```
+    pub fn get<'c, K>(
+        self,
+        db: impl PgAcquire<'c>,
+        key: impl Into<K>,
+    ) -> impl Future<Output = Result<Option<R>, OrmoluError>> + Send
+    where
+        T: HasPrimaryKey<K>,
+        K: ToSqlValue,
+    {
+        let column = ColRef::new(T::qualified_name(), T::primary_key_column());
+        self.filter_q(Where::eq(column, key.into())).first(db)
+    }
+
+    /// Reads the rows through a server-side cursor on `db`, see [`Cursor`].
+    ///
+    /// ```rust,ignore
+    /// let mut tx = pool.begin().await?;
+    /// let mut orders = orders.chunk_size(10_000).stream(&mut tx);
+    /// while let Some(order) = orders.try_next().await? {
+    ///     export.write(&order)?;
+    /// }
+    /// ```
+    pub fn stream<'c>(self, db: impl PgAcquire<'c> + 'c) -> Cursor<'c, R>
+    where
+        R: 'c,
+    {
+        let (sql, values) = self.state.to_sql();
+        let begin = Box::pin(async { Ok(db.begin().await?) });
+        Cursor::new(begin, sql, values, self.chunk_size)
+    }
+}
+
+impl<T: Table, R> QuerySet<T, R> {
+    /// Returns the number of rows the query matches without fetching them,
+    /// see [`QueryState::into_count_sql`].
+    pub fn count<'c>(
+        self,
+        db: impl PgAcquire<'c>,
+    ) -> impl Future<Output = Result<i64, OrmoluError>> + Send {
+        let (sql, values) = self.state.into_count_sql();
+        let arguments = SqlValue::into_arguments(values);
+
+        async move {
+            let mut conn = db.acquire().await?;
+
+            Ok(sqlx::query_scalar_with::<_, i64, _>(&sql, arguments)
+                .fetch_one(&mut *conn)
+                .await?)
+        }
+    }
+
+    /// Returns whether the query matches any row without fetching it.
+    pub fn exists<'c>(
+        self,
+        db: impl PgAcquire<'c>,
+    ) -> impl Future<Output = Result<bool, OrmoluError>> + Send {
+        let (sql, values) = self.state.into_exists_sql();
+        let arguments = SqlValue::into_arguments(values);
+
+        async move {
+            let mut conn = db.acquire().await?;
+
+            Ok(sqlx::query_scalar_with::<_, bool, _>(&sql, arguments)
+                .fetch_one(&mut *conn)
+                .await?)
+        }
+    }
+}
+
+impl<T: Table, R> std::fmt::Display for QuerySet<T, R> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.state)
     }
@@ -87,14 +272,29 @@ impl<T: Table> std::fmt::Display for QuerySet<T> {
 //     }
 // }
 
-impl<T> Stream for QuerySet<T>
+/// The query runs on the first poll on a connection of the table's pool, see
+/// [`GetConnectionPool`], rows are decoded as they are polled. Use
+/// [`QuerySet::stream`] to read them on a connection or in a transaction.
+impl<T, R> Stream for QuerySet<T, R>
 where
-    T: Table,
+    T: Table + Unpin + 'static,
+    R: for<'r> FromRow<'r, PgRow> + Send + Unpin + 'static,
 {
-    type Item = T;
+    type Item = Result<R, OrmoluError>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        todo!()
+        let this = self.get_mut();
+        let cursor = match &mut this.cursor {
+            Some(cursor) => cursor,
+            None => {
+                let (sql, values) = this.state.to_sql();
+                let begin = Box::pin(async { Ok(T::get_connection_pool().await?.begin().await?) });
+                this.cursor
+                    .insert(Cursor::new(begin, sql, values, this.chunk_size))
+            }
+        };
+
+        Pin::new(cursor).poll_next(cx)
     }
 }
 
@@ -103,40 +303,592 @@ where
 
 // TODO: impl Filter from Iterator?
 // AsyncIterator?
-impl<T: Table> QuerySet<T> {
+impl<T: Table, R> QuerySet<T, R> {
     /// Returns a QuerySet containing WHERE clauses derived from the filters.
     ///
     /// Filters are joined via AND in the underlying SQL statement.
     ///
     /// If you need to execute more complex queries
-    /// (for example, queries with OR statements), you can use the `.or()` syntax.
+    /// (for example, queries with OR statements), you can use [`QuerySet::filter_or`],
+    /// [`QuerySet::or`] or build a [`Q`] and pass it to [`QuerySet::filter_q`].
     // NOTE: this Shadows Iterator::filter
     // TODO: Change TFilter to AndFilter<T> with deref
     pub fn filter<P>(mut self, predicate: P) -> Self
     where
         P: Fn(T::Proxy) -> bool,
     {
-        let filter = FieldsFilter::new::<T, P>(predicate);
-        self.state
-            .where_conditions
-            .append(&mut filter.state.borrow_mut().clauses);
-
+        self.state.and_where(Self::trace(predicate));
         self
     }
 
-    // TODO: remove this - figure out how to check if || in statement
-    /// Returns a QuerySet containing WHERE clauses derived from the filters.
+    /// Returns a QuerySet where rows matching either the existing filters or
+    /// this predicate are selected.
     ///
     /// Filters are joined via OR in the underlying SQL statement.
     pub fn filter_or<P>(mut self, predicate: P) -> Self
     where
         P: Fn(T::Proxy) -> bool,
     {
-        let filter = FieldsFilter::new::<T, P>(predicate);
+        self.state.or_where(Self::trace(predicate));
+        self
+    }
+
+    /// Returns a QuerySet filtered by a condition built outside of a closure.
+    ///
+    /// ```rust,ignore
+    /// let q = Q::from(Where::eq("status", "shipped")) | Where::eq("status", "delivered");
+    /// let orders = orders.filter_q(!q);
+    /// ```
+    pub fn filter_q(mut self, condition: impl Into<Q>) -> Self {
+        self.state.and_where(condition);
+        self
+    }
+
+    /// Combines both QuerySets so rows matching either one are selected.
+    ///
+    /// ```rust,ignore
+    /// // (a OR b) AND c
+    /// let a_or_b = orders.filter(|o| o.status == "a").or(orders2.filter(|o| o.status == "b"));
+    /// let q = a_or_b.filter(|o| o.name.contains("c"));
+    /// ```
+    pub fn or(mut self, other: QuerySet<T, R>) -> Self {
+        self.state.where_clause = match (self.state.where_clause, other.state.where_clause) {
+            (Some(lhs), Some(rhs)) => Some(lhs.or(rhs)),
+            // one side has no filter so every row matches
+            _ => None,
+        };
+        self
+    }
+
+    /// Combines both QuerySets so only rows matching both are selected.
+    pub fn and(mut self, other: QuerySet<T, R>) -> Self {
+        if let Some(rhs) = other.state.where_clause {
+            self.state.and_where(rhs);
+        }
+        self
+    }
+
+    /// Joins `U` on the foreign key between the two tables, see [`Joinable`].
+    ///
+    /// ```rust,ignore
+    /// // ... FROM "public"."order" INNER JOIN "public"."customer"
+    /// //     ON "public"."order"."customer_id" = "public"."customer"."id"
+    /// let orders = orders.inner_join::<Customer, _>();
+    /// ```
+    pub fn inner_join<U, Via>(self) -> Self
+    where
+        T: Joinable<U, Via>,
+        U: Table,
+    {
+        self.join_related::<U, Via>(JoinType::Inner)
+    }
+
+    /// Joins `U` on the foreign key between the two tables, keeping rows of
+    /// this table that have no match.
+    pub fn left_join<U, Via>(self) -> Self
+    where
+        T: Joinable<U, Via>,
+        U: Table,
+    {
+        self.join_related::<U, Via>(JoinType::Left)
+    }
+
+    /// Joins `U` on the foreign key between the two tables, keeping rows of
+    /// `U` that have no match.
+    pub fn right_join<U, Via>(self) -> Self
+    where
+        T: Joinable<U, Via>,
+        U: Table,
+    {
+        self.join_related::<U, Via>(JoinType::Right)
+    }
+
+    /// Joins `U` on the foreign key between the two tables, keeping rows of
+    /// both tables that have no match.
+    pub fn full_join<U, Via>(self) -> Self
+    where
+        T: Joinable<U, Via>,
+        U: Table,
+    {
+        self.join_related::<U, Via>(JoinType::Full)
+    }
+
+    /// Pairs every row with every row of `U`.
+    pub fn cross_join<U: Table>(mut self) -> Self {
+        self.state.join(Join::cross(U::qualified_name()));
+        self
+    }
+
+    /// Joins `U` where the two picked columns are equal, for tables without a
+    /// foreign key between them or with more than one.
+    ///
+    /// ```rust,ignore
+    /// let orders = orders.join_on::<Customer, _, _>(JoinType::Left, |o, c| (o.billing_id, c.id));
+    /// ```
+    pub fn join_on<U, A, B>(
+        mut self,
+        join_type: JoinType,
+        columns: impl FnOnce(T::Proxy, U::Proxy) -> (Col<A>, Col<B>),
+    ) -> Self
+    where
+        U: Table,
+    {
+        let (lhs, rhs) = columns(
+            T::to_field_filter(Default::default()),
+            U::to_field_filter(Default::default()),
+        );
         self.state
-            .where_conditions
-            .append(&mut filter.state.borrow_mut().clauses);
+            .join(Join::on(join_type, lhs.column_ref(), rhs.column_ref()));
+        self
+    }
+
+    /// Selects columns of this table and a joined table `U`, rows are decoded
+    /// into a tuple of the column types.
+    ///
+    /// ```rust,ignore
+    /// let rows: Vec<(String, Unique<String>)> = orders
+    ///     .inner_join::<Customer, _>()
+    ///     .select_joined::<Customer, _>(|o, c| (o.name, c.email))
+    ///     .all(&pool)
+    ///     .await?;
+    /// ```
+    pub fn select_joined<U, C>(
+        mut self,
+        columns: impl FnOnce(T::Proxy, U::Proxy) -> C,
+    ) -> QuerySet<T, C::Output>
+    where
+        U: Table,
+        C: Columns,
+    {
+        let columns = columns(
+            T::to_field_filter(Default::default()),
+            U::to_field_filter(Default::default()),
+        );
+        self.state.command = Command::Select {
+            columns: columns.exprs(),
+        };
+        self.decode_as()
+    }
+
+    /// Selects only the fields set to `true`, rows are decoded into `R2`, a
+    /// tuple of the field types in declaration order or a [`FromRow`] struct
+    /// with fields named like the columns.
+    ///
+    /// Useful to skip large columns a query has no use for:
+    /// ```rust,ignore
+    /// let contacts: Vec<(String, Unique<String>)> = customers
+    ///     .select(|s| {
+    ///         s.first_name = true;
+    ///         s.email = true;
+    ///     })
+    ///     .all(&pool)
+    ///     .await?;
+    /// ```
+    ///
+    /// `R2` is not checked against the picked fields, decoding a field that was
+    /// not picked fails when the rows are fetched.
+    ///
+    /// # Panics
+    /// When no field is picked, a SELECT needs at least one column.
+    pub fn select<R2>(mut self, fields: impl FnOnce(&mut T::Select)) -> QuerySet<T, R2>
+    where
+        T: Selectable,
+    {
+        let mut select = T::select();
+        fields(&mut select);
+
+        let table = T::qualified_name();
+        let columns: Vec<_> = T::selected_fields(&select)
+            .into_iter()
+            .map(|field| ColRef::new(table, T::get_db_column_name(field)).into())
+            .collect();
+        assert!(!columns.is_empty(), "no field of {table} is selected");
+
+        self.state.command = Command::Select { columns };
+        self.decode_as()
+    }
+
+    /// Selects a single column to check another column against with
+    /// [`Col::is_in`].
+    ///
+    /// ```rust,ignore
+    /// // ... WHERE "id" IN (SELECT "public"."order"."customer_id" FROM "public"."order" ...)
+    /// let buyers = orders.filter(|o| o.total_amount > 100.0).values(|o| o.customer_id);
+    /// let customers = customers.filter(|c| c.id.is_in(&buyers));
+    /// ```
+    ///
+    /// The subquery is uncorrelated, it cannot refer to the rows of the query
+    /// it is used in and is run once for the whole statement.
+    ///
+    /// Rows where a nullable column is NULL are left out, a single NULL would
+    /// otherwise make every `NOT IN` unknown.
+    pub fn values<C: Nullable>(
+        mut self,
+        column: impl FnOnce(T::Proxy) -> Col<C>,
+    ) -> Subquery<C::Value> {
+        let column = column(T::to_field_filter(Default::default())).column_ref();
 
+        if C::NULLABLE {
+            self.state.and_where(Where::is_not_null(column.clone()));
+        }
+
+        self.state.command = Command::Select {
+            columns: vec![column.into()],
+        };
+        Subquery::new(self.state)
+    }
+
+    /// Decodes rows into `R2` instead, a [`FromRow`] struct with fields named
+    /// like the selected columns.
+    ///
+    /// ```rust,ignore
+    /// #[derive(sqlx::FromRow)]
+    /// struct OrderEmail {
+    ///     name: String,
+    ///     email: String,
+    /// }
+    ///
+    /// let rows = orders
+    ///     .inner_join::<Customer, _>()
+    ///     .select_joined::<Customer, _>(|o, c| (o.name, c.email))
+    ///     .decode_as::<OrderEmail>();
+    /// ```
+    pub fn decode_as<R2>(self) -> QuerySet<T, R2> {
+        QuerySet {
+            state: self.state,
+            all_rows: self.all_rows,
+            chunk_size: self.chunk_size,
+            cursor: None,
+            _row: PhantomData,
+        }
+    }
+
+    /// Groups rows that are equal on the picked columns, replacing any previous
+    /// grouping. Select the groups with [`QuerySet::aggregate`].
+    ///
+    /// ```rust,ignore
+    /// let totals: Vec<(String, Option<f64>)> = orders
+    ///     .group_by(|o| (o.status,))
+    ///     .aggregate(|o| (o.status, o.total_amount.sum()))
+    ///     .all(&pool)
+    ///     .await?;
+    /// ```
+    pub fn group_by<C: Columns>(mut self, columns: impl FnOnce(T::Proxy) -> C) -> Self {
+        let columns = columns(T::to_field_filter(Default::default()));
+        self.state.group_by = Some(GroupBy::new(columns.exprs()));
+        self
+    }
+
+    /// Returns a QuerySet containing HAVING clauses derived from the filters,
+    /// comparisons on aggregates apply to whole groups.
+    ///
+    /// ```rust,ignore
+    /// let busy = orders
+    ///     .group_by(|o| (o.customer_id,))
+    ///     .having(|o| o.id.count() >= 10 && o.total_amount.avg() > 20.0);
+    /// ```
+    pub fn having<P>(mut self, predicate: P) -> Self
+    where
+        P: Fn(T::Proxy) -> bool,
+    {
+        self.state.and_having(Self::trace(predicate));
+        self
+    }
+
+    /// Selects the picked columns and aggregates, rows are decoded into a
+    /// tuple of their types.
+    ///
+    /// Without a [`QuerySet::group_by`] the aggregates are computed over every
+    /// matching row:
+    /// ```rust,ignore
+    /// let (count, newest) = customers
+    ///     .aggregate(|c| (c.id.count(), c.created_at.max()))
+    ///     .all(&pool)
+    ///     .await?[0];
+    /// ```
+    pub fn aggregate<C: Columns>(
+        mut self,
+        columns: impl FnOnce(T::Proxy) -> C,
+    ) -> QuerySet<T, C::Output> {
+        let columns = columns(T::to_field_filter(Default::default()));
+        self.state.command = Command::Select {
+            columns: columns.exprs(),
+        };
+        self.decode_as()
+    }
+
+    /// Returns at most `rows` rows.
+    ///
+    /// ```rust,ignore
+    /// let page = orders.order_by_asc(|o| o.id).offset(40).limit(20);
+    /// ```
+    pub fn limit(mut self, rows: i64) -> Self {
+        self.state.limit = Some(rows);
+        self
+    }
+
+    /// Skips the first `rows` rows.
+    pub fn offset(mut self, rows: i64) -> Self {
+        self.state.offset = Some(rows);
+        self
+    }
+
+    /// Lowers the limit to `rows` unless it already is lower.
+    fn limit_to(mut self, rows: i64) -> Self {
+        self.state.limit = Some(self.state.limit.map_or(rows, |limit| limit.min(rows)));
+        self
+    }
+
+    /// Sets how many rows are fetched at a time when the QuerySet is streamed,
+    /// [`DEFAULT_CHUNK_SIZE`] otherwise.
+    ///
+    /// ```rust,ignore
+    /// let mut rows = orders.chunk_size(10_000);
+    /// while let Some(order) = rows.try_next().await? {
+    ///     export.write(&order)?;
+    /// }
+    /// ```
+    pub fn chunk_size(mut self, rows: usize) -> Self {
+        self.chunk_size = rows;
+        self
+    }
+
+    /// Allows [`QuerySet::update`] and [`QuerySet::delete`] to run without a
+    /// filter, they refuse to touch every row of a table otherwise.
+    ///
+    /// ```rust,ignore
+    /// Session::query().all_rows().delete(&pool).await?;
+    /// ```
+    pub fn all_rows(mut self) -> Self {
+        self.all_rows = true;
+        self
+    }
+
+    /// Sets columns on every row matching the filters and returns the number of
+    /// rows that were updated.
+    ///
+    /// ```rust,ignore
+    /// let shipped = orders
+    ///     .filter(|o| o.status == "paid")
+    ///     .update(&pool, |o| o.status.set("shipped".to_string()))
+    ///     .await?;
+    /// ```
+    ///
+    /// # Errors
+    /// [`OrmoluError::Unfiltered`] when there is no filter, see [`QuerySet::all_rows`],
+    /// and the errors of [`QuerySet::into_update`].
+    pub fn update<'c>(
+        self,
+        db: impl PgAcquire<'c>,
+        assignments: impl FnOnce(T::Proxy),
+    ) -> impl Future<Output = Result<u64, OrmoluError>> + Send {
+        let update = self
+            .into_update(assignments)
+            .map(|update| update.execute(db));
+        async move { update?.await }
+    }
+
+    /// Builds the UPDATE run by [`QuerySet::update`] without running it.
+    ///
+    /// # Errors
+    /// [`OrmoluError::UnsupportedClause`] when the query joins, orders, groups
+    /// or limits its rows, an UPDATE would write every row matching the
+    /// filters instead.
+    pub fn into_update(
+        self,
+        assignments: impl FnOnce(T::Proxy),
+    ) -> Result<QueryState<T>, OrmoluError> {
+        self.check_filters_only("update")?;
+        let where_clause = self.checked_where_clause("update")?;
+
+        let state = Rc::new(RefCell::new(FilterState::default()));
+        assignments(T::to_field_filter(state.clone()));
+
+        let mut update =
+            QueryState::new_update(std::mem::take(&mut state.borrow_mut().assignments));
+        update.where_clause = where_clause;
+        Ok(update)
+    }
+
+    /// Deletes every row matching the filters and returns the number of rows
+    /// that were deleted.
+    ///
+    /// ```rust,ignore
+    /// let removed = orders.filter(|o| o.test == true).delete(&pool).await?;
+    /// ```
+    ///
+    /// # Errors
+    /// [`OrmoluError::Unfiltered`] when there is no filter, see [`QuerySet::all_rows`],
+    /// and the errors of [`QuerySet::into_delete`].
+    pub fn delete<'c>(
+        self,
+        db: impl PgAcquire<'c>,
+    ) -> impl Future<Output = Result<u64, OrmoluError>> + Send {
+        let delete = self.into_delete().map(|delete| delete.execute(db));
+        async move { delete?.await }
+    }
+
+    /// Builds the DELETE run by [`QuerySet::delete`] without running it.
+    ///
+    /// # Errors
+    /// [`OrmoluError::UnsupportedClause`] when the query joins, orders, groups
+    /// or limits its rows, `orders.limit(10).delete(&pool)` would delete every
+    /// matching row instead of ten.
+    pub fn into_delete(self) -> Result<QueryState<T>, OrmoluError> {
+        self.check_filters_only("delete")?;
+        let mut delete = QueryState::new_delete();
+        delete.where_clause = self.checked_where_clause("delete")?;
+        Ok(delete)
+    }
+
+    /// Orders the rows by a column in ascending order, replacing any previous ordering.
+    ///
+    /// ```rust,ignore
+    /// let orders = john.orders().order_by_asc(|o| o.order_date);
+    /// ```
+    pub fn order_by_asc<F, C>(self, column: F) -> Self
+    where
+        F: FnOnce(T::Proxy) -> Col<C>,
+    {
+        self.order_by(column, SortDirection::Asc)
+    }
+
+    /// Orders the rows by a column in descending order, replacing any previous ordering.
+    pub fn order_by_desc<F, C>(self, column: F) -> Self
+    where
+        F: FnOnce(T::Proxy) -> Col<C>,
+    {
+        self.order_by(column, SortDirection::Desc)
+    }
+
+    /// Orders rows that are equal on the previous columns by this column, ascending.
+    ///
+    /// ```rust,ignore
+    /// let customers = customers
+    ///     .order_by_asc(|c| c.last_name)
+    ///     .then_by(|c| c.first_name)
+    ///     .then_by_desc(|c| c.created_at)
+    ///     .nulls_last();
+    /// ```
+    pub fn then_by<F, C>(self, column: F) -> Self
+    where
+        F: FnOnce(T::Proxy) -> Col<C>,
+    {
+        self.then_order_by(column, SortDirection::Asc)
+    }
+
+    /// Orders rows that are equal on the previous columns by this column, descending.
+    pub fn then_by_desc<F, C>(self, column: F) -> Self
+    where
+        F: FnOnce(T::Proxy) -> Col<C>,
+    {
+        self.then_order_by(column, SortDirection::Desc)
+    }
+
+    /// Puts NULL values of the last ordered column before all other values.
+    pub fn nulls_first(self) -> Self {
+        self.nulls(NullsOrder::First)
+    }
+
+    /// Puts NULL values of the last ordered column after all other values.
+    pub fn nulls_last(self) -> Self {
+        self.nulls(NullsOrder::Last)
+    }
+
+    fn order_by<F, C>(mut self, column: F, direction: SortDirection) -> Self
+    where
+        F: FnOnce(T::Proxy) -> Col<C>,
+    {
+        self.state.order_by = Some(OrderBy::new(Self::order_by_term(column, direction)));
+        self
+    }
+
+    fn then_order_by<F, C>(mut self, column: F, direction: SortDirection) -> Self
+    where
+        F: FnOnce(T::Proxy) -> Col<C>,
+    {
+        let term = Self::order_by_term(column, direction);
+        match &mut self.state.order_by {
+            Some(order_by) => order_by.then(term),
+            None => self.state.order_by = Some(OrderBy::new(term)),
+        }
         self
     }
+
+    fn nulls(mut self, nulls: NullsOrder) -> Self {
+        if let Some(term) = self.state.order_by.as_mut().and_then(OrderBy::last_mut) {
+            term.nulls = Some(nulls);
+        }
+        self
+    }
+
+    fn order_by_term<F, C>(column: F, direction: SortDirection) -> OrderByTerm
+    where
+        F: FnOnce(T::Proxy) -> Col<C>,
+    {
+        // the proxy is only used to pick a column, nothing is recorded on it
+        let column = column(T::to_field_filter(Default::default()));
+
+        OrderByTerm {
+            column: column.column_ref(),
+            direction,
+            nulls: None,
+        }
+    }
+
+    fn join_related<U, Via>(mut self, join_type: JoinType) -> Self
+    where
+        T: Joinable<U, Via>,
+        U: Table,
+    {
+        let (lhs, rhs) = T::join_columns();
+        self.state.join(Join::on(join_type, lhs, rhs));
+        self
+    }
+
+    /// The filters an UPDATE or DELETE is limited to.
+    ///
+    /// A closure that always returns `true` traces to [`Q::TRUE`], which matches
+    /// every row just like no filter at all.
+    /// Refuses a query with clauses only a SELECT can honour.
+    fn check_filters_only(&self, statement: &'static str) -> Result<(), OrmoluError> {
+        let state = &self.state;
+        let clause = [
+            (!state.joins.is_empty(), "a join"),
+            (state.order_by.is_some(), "ORDER BY"),
+            (state.group_by.is_some(), "GROUP BY"),
+            (state.having.is_some(), "HAVING"),
+            (state.limit.is_some(), "LIMIT"),
+            (state.offset.is_some(), "OFFSET"),
+        ]
+        .into_iter()
+        .find_map(|(set, clause)| set.then_some(clause));
+
+        match clause {
+            Some(clause) => Err(OrmoluError::UnsupportedClause {
+                statement,
+                table: state.table,
+                clause,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn checked_where_clause(self, statement: &'static str) -> Result<Option<Q>, OrmoluError> {
+        match self.state.where_clause {
+            Some(condition) if condition != Q::TRUE => Ok(Some(condition)),
+            _ if self.all_rows => Ok(None),
+            _ => Err(OrmoluError::Unfiltered {
+                statement,
+                table: self.state.table,
+            }),
+        }
+    }
+
+    fn trace<P>(predicate: P) -> Q
+    where
+        P: Fn(T::Proxy) -> bool,
+    {
+        FieldsFilter::new::<T, P>(predicate).condition
+    }
 }
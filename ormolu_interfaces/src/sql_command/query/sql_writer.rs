@@ -0,0 +1,107 @@
+use crate::SqlValue;
+
+/// Accumulates the SQL text of a statement together with its bind parameters.
+///
+/// Values are never written into the text, [`SqlWriter::push_value`] writes the
+/// next `$n` placeholder and keeps the value so it can be bound through sqlx.
+#[derive(Default)]
+pub struct SqlWriter {
+    sql: String,
+    values: Vec<SqlValue>,
+    /// Whether columns are written as `"table"."column"`
+    qualify_columns: bool,
+    /// `(value index, slot)` of every placeholder written by [`SqlWriter::push_slot`]
+    slots: Vec<(usize, usize)>,
+}
+
+impl SqlWriter {
+    /// Sets whether columns that know their table are prefixed with it.
+    pub fn qualify_columns(&mut self, qualify: bool) {
+        self.qualify_columns = qualify;
+    }
+
+    pub fn qualifies_columns(&self) -> bool {
+        self.qualify_columns
+    }
+
+    pub fn push(&mut self, sql: &str) {
+        self.sql.push_str(sql);
+    }
+
+    /// Writes a double quoted identifier so keywords like `order` and mixed case
+    /// names are taken literally by Postgres.
+    pub fn push_ident(&mut self, ident: &str) {
+        self.sql.push_str(&quote_ident(ident));
+    }
+
+    /// Writes a `schema.object` name with each part quoted.
+    pub fn push_qualified_ident(&mut self, qualified_name: &str) {
+        for (i, part) in qualified_name.split('.').enumerate() {
+            if i > 0 {
+                self.sql.push('.');
+            }
+            self.push_ident(part);
+        }
+    }
+
+    /// Writes a comma separated list of quoted identifiers.
+    pub fn push_ident_list(&mut self, idents: &[&str]) {
+        for (i, ident) in idents.iter().enumerate() {
+            if i > 0 {
+                self.sql.push_str(", ");
+            }
+            self.push_ident(ident);
+        }
+    }
+
+    /// Writes a `$n` placeholder and stores the value bound to it.
+    pub fn push_value(&mut self, value: SqlValue) {
+        self.values.push(value);
+        self.sql.push('$');
+        self.sql.push_str(&self.values.len().to_string());
+    }
+
+    /// Writes a `$n` placeholder for a value that is only known when a
+    /// [`PreparedStatement`](super::PreparedStatement) is bound.
+    pub fn push_slot(&mut self, slot: usize) {
+        self.slots.push((self.values.len(), slot));
+        self.push_value(SqlValue::Null);
+    }
+
+    /// Returns the SQL text and the values in placeholder order.
+    pub fn finish(self) -> (String, Vec<SqlValue>) {
+        (self.sql, self.values)
+    }
+
+    /// Returns the SQL text, the values in placeholder order and which of them
+    /// stand for a slot.
+    pub(crate) fn finish_with_slots(self) -> (String, Vec<SqlValue>, Vec<(usize, usize)>) {
+        (self.sql, self.values, self.slots)
+    }
+}
+
+/// Double quotes an identifier, escaping any embedded quotes.
+///
+/// see: <https://www.postgresql.org/docs/current/sql-syntax-lexical.html#SQL-SYNTAX-IDENTIFIERS>
+pub fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod sql_writer_tests {
+    use super::*;
+
+    #[test]
+    fn quotes_identifiers() {
+        assert_eq!(quote_ident("order"), "\"order\"");
+        assert_eq!(quote_ident("CustomerId"), "\"CustomerId\"");
+        assert_eq!(quote_ident("we\"ird"), "\"we\"\"ird\"");
+    }
+
+    #[test]
+    fn qualified_names_quote_each_part() {
+        let mut w = SqlWriter::default();
+        w.push_qualified_ident("private.product");
+        assert_eq!(w.finish().0, "\"private\".\"product\"");
+    }
+}
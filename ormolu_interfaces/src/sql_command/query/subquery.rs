@@ -0,0 +1,70 @@
+use super::{QueryState, SqlWriter};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// A SELECT of a single column nested in another statement, `V` is the type of
+/// the values it returns. Created with [`QuerySet::values`](super::QuerySet::values).
+///
+/// ```rust,ignore
+/// let buyers = orders.filter(|o| o.total_amount > 100.0).values(|o| o.customer_id);
+/// let customers = customers.filter(|c| c.id.is_in(&buyers));
+/// ```
+pub struct Subquery<V> {
+    statement: Arc<QueryState<()>>,
+    _value: PhantomData<fn() -> V>,
+}
+
+impl<V> Subquery<V> {
+    pub(crate) fn new<T>(statement: QueryState<T>) -> Self {
+        Self {
+            statement: Arc::new(statement.erase()),
+            _value: PhantomData,
+        }
+    }
+
+    /// Forgets the value type once it has been checked against the column.
+    pub fn untyped(&self) -> Subquery<()> {
+        Subquery {
+            statement: self.statement.clone(),
+            _value: PhantomData,
+        }
+    }
+
+    /// Writes `(SELECT ...)`, its columns are always qualified so they cannot be
+    /// mistaken for columns of the outer statement.
+    pub fn write_sql(&self, w: &mut SqlWriter) {
+        let qualify_columns = w.qualifies_columns();
+
+        w.push("(");
+        self.statement.write_sql(w, true);
+        w.push(")");
+
+        w.qualify_columns(qualify_columns);
+    }
+}
+
+impl<V> Clone for Subquery<V> {
+    fn clone(&self) -> Self {
+        Self {
+            statement: self.statement.clone(),
+            _value: PhantomData,
+        }
+    }
+}
+
+impl<V> std::fmt::Debug for Subquery<V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Subquery")
+            .field(&self.statement.to_sql())
+            .finish()
+    }
+}
+
+/// Two subqueries are equal when they render the same statement, so the same
+/// subquery built on every run of a filter closure is recognised.
+impl<V> PartialEq for Subquery<V> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.statement, &other.statement)
+            || self.statement.to_sql() == other.statement.to_sql()
+    }
+}
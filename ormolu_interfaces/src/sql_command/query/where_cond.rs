@@ -1,4 +1,8 @@
-#[derive(Debug, Clone)]
+use super::{Expr, SqlWriter, Subquery};
+use crate::{OrmoluError, SqlValue, ToSqlValue};
+use std::mem::discriminant;
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum WhereOp {
     EqualTo,
     GreaterThan,
@@ -6,6 +10,17 @@ pub enum WhereOp {
     GreaterThanOrEqualTo,
     LessThanOrEqualTo,
     Like,
+    /// Case-insensitive `LIKE`
+    ILike,
+    /// POSIX regular expression match, `~`
+    Regex,
+    /// Case-insensitive POSIX regular expression match, `~*`
+    IRegex,
+    SimilarTo,
+    /// Membership in an array or the rows of a subquery
+    In,
+    /// Compares nothing, the condition's value is ignored
+    IsNull,
     Not(Box<WhereOp>),
 }
 
@@ -18,6 +33,12 @@ impl std::fmt::Display for WhereOp {
             WhereOp::GreaterThanOrEqualTo => ">=",
             WhereOp::LessThanOrEqualTo => "<=",
             WhereOp::Like => "LIKE",
+            WhereOp::ILike => "ILIKE",
+            WhereOp::Regex => "~",
+            WhereOp::IRegex => "~*",
+            WhereOp::SimilarTo => "SIMILAR TO",
+            WhereOp::In => "IN",
+            WhereOp::IsNull => "IS NULL",
             WhereOp::Not(nop) => match &**nop {
                 WhereOp::EqualTo => "!=",
                 WhereOp::GreaterThan => "<=",
@@ -25,6 +46,12 @@ impl std::fmt::Display for WhereOp {
                 WhereOp::LessThan => ">=",
                 WhereOp::LessThanOrEqualTo => ">",
                 WhereOp::Like => "NOT LIKE",
+                WhereOp::ILike => "NOT ILIKE",
+                WhereOp::Regex => "!~",
+                WhereOp::IRegex => "!~*",
+                WhereOp::SimilarTo => "NOT SIMILAR TO",
+                WhereOp::In => "NOT IN",
+                WhereOp::IsNull => "IS NOT NULL",
                 WhereOp::Not(_) => {
                     unreachable!("wrap_not will unbox WhereOp::Not so this will never happen")
                 }
@@ -35,49 +62,285 @@ impl std::fmt::Display for WhereOp {
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct Where {
-    oper: WhereOp,
-    column: &'static str,
-    // its always going to be string since it will be converted to string anyways
-    value: String,
+/// The right side of a [`Where`] condition.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    /// Sent as a bind parameter, never written into the statement text
+    Value(SqlValue),
+    /// Another column or a computed value
+    Expr(Expr),
+    Subquery(Subquery<()>),
 }
 
-impl std::fmt::Display for Where {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let col = &self.column;
-        let value = &self.value;
-        let op = self.oper.to_string();
+impl Operand {
+    fn write_sql(&self, w: &mut SqlWriter) {
+        match self {
+            Operand::Value(value) => w.push_value(value.clone()),
+            Operand::Expr(expr) => expr.write_sql(w),
+            Operand::Subquery(subquery) => subquery.write_sql(w),
+        }
+    }
+}
 
-        write!(f, "{col} {op} {value}")
+impl From<SqlValue> for Operand {
+    fn from(value: SqlValue) -> Self {
+        Operand::Value(value)
+    }
+}
+
+impl From<Expr> for Operand {
+    fn from(expr: Expr) -> Self {
+        match expr {
+            Expr::Value(value) => Operand::Value(value),
+            expr => Operand::Expr(expr),
+        }
     }
 }
 
+impl<V> From<Subquery<V>> for Operand {
+    fn from(subquery: Subquery<V>) -> Self {
+        Operand::Subquery(subquery.untyped())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Where {
+    oper: WhereOp,
+    column: Expr,
+    value: Operand,
+    /// Whether the column can be NULL, a negated comparison then also matches
+    /// the NULL rows the comparison itself leaves out
+    nullable: bool,
+}
+
 impl Where {
-    pub fn like(column: &'static str, value: String) -> Self {
+    /// Writes `{col} {op} $n` and binds the value to the placeholder.
+    ///
+    /// `col = $1` is unknown for a NULL column, so on a nullable column the
+    /// negation is written `col IS DISTINCT FROM $1` (or `col op $1 OR col IS NULL`)
+    /// to match every row the comparison does not.
+    pub fn write_sql(&self, w: &mut SqlWriter) {
+        match &self.oper {
+            WhereOp::IsNull => {
+                self.column.write_sql(w);
+                w.push(" IS NULL");
+            }
+            WhereOp::Not(op) if **op == WhereOp::IsNull => {
+                self.column.write_sql(w);
+                w.push(" IS NOT NULL");
+            }
+            WhereOp::Not(op) if self.nullable && **op == WhereOp::EqualTo => {
+                self.column.write_sql(w);
+                w.push(" IS DISTINCT FROM ");
+                self.value.write_sql(w);
+            }
+            WhereOp::Not(_) if self.nullable => {
+                w.push("(");
+                self.write_comparison(w);
+                w.push(" OR ");
+                self.column.write_sql(w);
+                w.push(" IS NULL)");
+            }
+            _ => self.write_comparison(w),
+        }
+    }
+
+    fn write_comparison(&self, w: &mut SqlWriter) {
+        let (is_in, negated) = match &self.oper {
+            WhereOp::In => (true, false),
+            WhereOp::Not(op) => (**op == WhereOp::In, true),
+            _ => (false, false),
+        };
+
+        match &self.value {
+            // nothing is in an empty list, which has no element type to bind it with
+            Operand::Value(SqlValue::Array(values)) if is_in && values.is_empty() => {
+                w.push(if negated { "TRUE" } else { "FALSE" });
+            }
+            // one array parameter instead of a placeholder per value
+            Operand::Value(array @ SqlValue::Array(_)) if is_in => {
+                self.column.write_sql(w);
+                w.push(if negated { " <> ALL(" } else { " = ANY(" });
+                w.push_value(array.clone());
+                w.push(")");
+            }
+            value => {
+                self.column.write_sql(w);
+                w.push(&format!(" {} ", self.oper));
+                value.write_sql(w);
+            }
+        }
+    }
+
+    /// Compares `column` to a value, another column or a computed value.
+    pub fn new(oper: WhereOp, column: impl Into<Expr>, value: impl Into<Operand>) -> Self {
+        Where {
+            oper,
+            column: column.into(),
+            value: value.into(),
+            nullable: false,
+        }
+    }
+
+    pub fn like(column: impl Into<Expr>, value: impl ToSqlValue) -> Self {
         Where {
             oper: WhereOp::Like,
-            column,
-            value,
+            column: column.into(),
+            value: value.to_sql_value().into(),
+            nullable: false,
         }
     }
 
-    pub fn eq(column: &'static str, value: String) -> Self {
+    pub fn ilike(column: impl Into<Expr>, value: impl ToSqlValue) -> Self {
+        Where {
+            oper: WhereOp::ILike,
+            column: column.into(),
+            value: value.to_sql_value().into(),
+            nullable: false,
+        }
+    }
+
+    pub fn regex(column: impl Into<Expr>, pattern: impl ToSqlValue) -> Self {
+        Where {
+            oper: WhereOp::Regex,
+            column: column.into(),
+            value: pattern.to_sql_value().into(),
+            nullable: false,
+        }
+    }
+
+    pub fn iregex(column: impl Into<Expr>, pattern: impl ToSqlValue) -> Self {
+        Where {
+            oper: WhereOp::IRegex,
+            column: column.into(),
+            value: pattern.to_sql_value().into(),
+            nullable: false,
+        }
+    }
+
+    pub fn similar_to(column: impl Into<Expr>, pattern: impl ToSqlValue) -> Self {
+        Where {
+            oper: WhereOp::SimilarTo,
+            column: column.into(),
+            value: pattern.to_sql_value().into(),
+            nullable: false,
+        }
+    }
+
+    pub fn eq(column: impl Into<Expr>, value: impl ToSqlValue) -> Self {
         Where {
             oper: WhereOp::EqualTo,
-            column,
-            value,
+            column: column.into(),
+            value: value.to_sql_value().into(),
+            nullable: false,
         }
     }
 
-    pub fn neq(column: &'static str, value: String) -> Self {
+    pub fn neq(column: impl Into<Expr>, value: impl ToSqlValue) -> Self {
         Where {
             oper: WhereOp::Not(Box::new(WhereOp::EqualTo)),
-            column,
+            column: column.into(),
+            value: value.to_sql_value().into(),
+            nullable: false,
+        }
+    }
+
+    pub fn gt(column: impl Into<Expr>, value: impl ToSqlValue) -> Self {
+        Where {
+            oper: WhereOp::GreaterThan,
+            column: column.into(),
+            value: value.to_sql_value().into(),
+            nullable: false,
+        }
+    }
+
+    pub fn gte(column: impl Into<Expr>, value: impl ToSqlValue) -> Self {
+        Where {
+            oper: WhereOp::GreaterThanOrEqualTo,
+            column: column.into(),
+            value: value.to_sql_value().into(),
+            nullable: false,
+        }
+    }
+
+    pub fn lt(column: impl Into<Expr>, value: impl ToSqlValue) -> Self {
+        Where {
+            oper: WhereOp::LessThan,
+            column: column.into(),
+            value: value.to_sql_value().into(),
+            nullable: false,
+        }
+    }
+
+    pub fn lte(column: impl Into<Expr>, value: impl ToSqlValue) -> Self {
+        Where {
+            oper: WhereOp::LessThanOrEqualTo,
+            column: column.into(),
+            value: value.to_sql_value().into(),
+            nullable: false,
+        }
+    }
+
+    /// `column = ANY($1)` for an array value, `column IN (...)` for a subquery.
+    ///
+    /// # Errors
+    /// [`OrmoluError::InvalidList`] when the value is not an array, or its
+    /// elements are NULL, arrays or of different types.
+    pub fn is_in(column: impl Into<Expr>, values: impl Into<Operand>) -> Result<Self, OrmoluError> {
+        let value = values.into();
+
+        if let Operand::Value(value) = &value {
+            let valid = match value {
+                SqlValue::Array(values) => values.iter().all(|element| {
+                    !matches!(element, SqlValue::Null | SqlValue::Array(_))
+                        && discriminant(element) == discriminant(&values[0])
+                }),
+                _ => false,
+            };
+
+            if !valid {
+                return Err(OrmoluError::InvalidList(value.clone()));
+            }
+        }
+
+        Ok(Where {
+            oper: WhereOp::In,
+            column: column.into(),
             value,
+            nullable: false,
+        })
+    }
+
+    /// `column IN (SELECT ...)`, [`Where::is_in`] for a subquery, which always
+    /// succeeds.
+    pub fn in_subquery<V>(column: impl Into<Expr>, subquery: Subquery<V>) -> Self {
+        Where {
+            oper: WhereOp::In,
+            column: column.into(),
+            value: subquery.into(),
+            nullable: false,
+        }
+    }
+
+    pub fn is_null(column: impl Into<Expr>) -> Self {
+        Where {
+            oper: WhereOp::IsNull,
+            column: column.into(),
+            value: SqlValue::Null.into(),
+            nullable: true,
         }
     }
 
+    pub fn is_not_null(column: impl Into<Expr>) -> Self {
+        Self::is_null(column).wrap_not()
+    }
+
+    /// Marks the column as nullable, see [`Where::write_sql`].
+    pub fn nullable(mut self) -> Self {
+        self.nullable = true;
+        self
+    }
+
     pub fn wrap_not(mut self) -> Self {
         if let WhereOp::Not(op) = self.oper {
             self.oper = *op;
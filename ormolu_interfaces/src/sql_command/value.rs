@@ -0,0 +1,207 @@
+use sqlx::{
+    Arguments, Encode, Postgres, Type,
+    encode::IsNull,
+    postgres::{PgArgumentBuffer, PgArguments, PgTypeInfo, types::Oid},
+    types::chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc},
+};
+
+/// A value that is sent to the database as a bind parameter (`$1`, `$2`, ...)
+/// instead of being interpolated into the SQL text.
+///
+/// Every literal a query needs is collected into a `Vec<SqlValue>` while the
+/// statement is rendered, see [`QueryState::to_sql`](crate::query::QueryState::to_sql).
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlValue {
+    /// An untyped `NULL`, Postgres will infer the type from the context.
+    Null,
+    Bool(bool),
+    SmallInt(i16),
+    Integer(i32),
+    BigInt(i64),
+    Real(f32),
+    Double(f64),
+    Text(String),
+    Bytes(Vec<u8>),
+    Date(NaiveDate),
+    Time(NaiveTime),
+    Timestamp(NaiveDateTime),
+    TimestampTz(DateTime<Utc>),
+    /// A Postgres array, every element has the same variant
+    Array(Vec<SqlValue>),
+}
+
+impl SqlValue {
+    /// Converts the collected values into sqlx arguments, in placeholder order.
+    pub fn into_arguments(values: Vec<SqlValue>) -> PgArguments {
+        let mut arguments = PgArguments::default();
+        for value in values {
+            arguments.add(value);
+        }
+
+        arguments
+    }
+}
+
+impl Type<Postgres> for SqlValue {
+    // the real type is value dependent and comes from `Encode::produces`
+    fn type_info() -> PgTypeInfo {
+        PgTypeInfo::with_oid(Oid(0))
+    }
+}
+
+impl<'q> Encode<'q, Postgres> for SqlValue {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> IsNull {
+        match self {
+            SqlValue::Null => IsNull::Yes,
+            SqlValue::Bool(v) => <bool as Encode<Postgres>>::encode_by_ref(v, buf),
+            SqlValue::SmallInt(v) => <i16 as Encode<Postgres>>::encode_by_ref(v, buf),
+            SqlValue::Integer(v) => <i32 as Encode<Postgres>>::encode_by_ref(v, buf),
+            SqlValue::BigInt(v) => <i64 as Encode<Postgres>>::encode_by_ref(v, buf),
+            SqlValue::Real(v) => <f32 as Encode<Postgres>>::encode_by_ref(v, buf),
+            SqlValue::Double(v) => <f64 as Encode<Postgres>>::encode_by_ref(v, buf),
+            SqlValue::Text(v) => <String as Encode<Postgres>>::encode_by_ref(v, buf),
+            SqlValue::Bytes(v) => <Vec<u8> as Encode<Postgres>>::encode_by_ref(v, buf),
+            SqlValue::Date(v) => <NaiveDate as Encode<Postgres>>::encode_by_ref(v, buf),
+            SqlValue::Time(v) => <NaiveTime as Encode<Postgres>>::encode_by_ref(v, buf),
+            SqlValue::Timestamp(v) => <NaiveDateTime as Encode<Postgres>>::encode_by_ref(v, buf),
+            SqlValue::TimestampTz(v) => <DateTime<Utc> as Encode<Postgres>>::encode_by_ref(v, buf),
+            SqlValue::Array(values) => encode_array(values, buf),
+        }
+    }
+
+    fn produces(&self) -> Option<PgTypeInfo> {
+        let type_info = match self {
+            SqlValue::Null => return None,
+            SqlValue::Bool(_) => <bool as Type<Postgres>>::type_info(),
+            SqlValue::SmallInt(_) => <i16 as Type<Postgres>>::type_info(),
+            SqlValue::Integer(_) => <i32 as Type<Postgres>>::type_info(),
+            SqlValue::BigInt(_) => <i64 as Type<Postgres>>::type_info(),
+            SqlValue::Real(_) => <f32 as Type<Postgres>>::type_info(),
+            SqlValue::Double(_) => <f64 as Type<Postgres>>::type_info(),
+            SqlValue::Text(_) => <String as Type<Postgres>>::type_info(),
+            SqlValue::Bytes(_) => <Vec<u8> as Type<Postgres>>::type_info(),
+            SqlValue::Date(_) => <NaiveDate as Type<Postgres>>::type_info(),
+            SqlValue::Time(_) => <NaiveTime as Type<Postgres>>::type_info(),
+            SqlValue::Timestamp(_) => <NaiveDateTime as Type<Postgres>>::type_info(),
+            SqlValue::TimestampTz(_) => <DateTime<Utc> as Type<Postgres>>::type_info(),
+            SqlValue::Array(values) => return array_type_info(values),
+        };
+
+        Some(type_info)
+    }
+}
+
+macro_rules! impl_array {
+    ($($variant:ident => $ty:ty),* $(,)?) => {
+        /// Encodes the elements as an array of their Rust type.
+        ///
+        /// # Panics
+        /// When the elements are not all the same variant,
+        /// [`Where::is_in`](crate::query::Where::is_in) refuses such arrays
+        /// before they are bound.
+        fn encode_array(values: &[SqlValue], buf: &mut PgArgumentBuffer) -> IsNull {
+            match values.first() {
+                $(
+                    Some(SqlValue::$variant(_)) => {
+                        let array: Vec<$ty> = values
+                            .iter()
+                            .map(|value| match value {
+                                SqlValue::$variant(v) => v.clone(),
+                                other => panic!(
+                                    "array elements have to be {}, found {other:?}",
+                                    stringify!($variant)
+                                ),
+                            })
+                            .collect();
+                        <Vec<$ty> as Encode<Postgres>>::encode_by_ref(&array, buf)
+                    }
+                )*
+                // an empty array has no element type to encode, statements never bind one
+                _ => IsNull::Yes,
+            }
+        }
+
+        fn array_type_info(values: &[SqlValue]) -> Option<PgTypeInfo> {
+            match values.first() {
+                $(Some(SqlValue::$variant(_)) => Some(<Vec<$ty> as Type<Postgres>>::type_info()),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+impl_array! {
+    Bool => bool,
+    SmallInt => i16,
+    Integer => i32,
+    BigInt => i64,
+    Real => f32,
+    Double => f64,
+    Text => String,
+    Bytes => Vec<u8>,
+    Date => NaiveDate,
+    Time => NaiveTime,
+    Timestamp => NaiveDateTime,
+    TimestampTz => DateTime<Utc>,
+}
+
+/// Converts a Rust value into a [`SqlValue`] so it can be bound to a statement.
+///
+/// This takes `&self` so values can be read straight off of a model instance.
+pub trait ToSqlValue {
+    fn to_sql_value(&self) -> SqlValue;
+}
+
+macro_rules! impl_to_sql_value {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl ToSqlValue for $ty {
+                fn to_sql_value(&self) -> SqlValue {
+                    SqlValue::$variant(self.clone())
+                }
+            }
+        )*
+    };
+}
+
+impl_to_sql_value! {
+    bool => Bool,
+    i16 => SmallInt,
+    i32 => Integer,
+    i64 => BigInt,
+    f32 => Real,
+    f64 => Double,
+    String => Text,
+    Vec<u8> => Bytes,
+    NaiveDate => Date,
+    NaiveTime => Time,
+    NaiveDateTime => Timestamp,
+    DateTime<Utc> => TimestampTz,
+}
+
+impl ToSqlValue for str {
+    fn to_sql_value(&self) -> SqlValue {
+        SqlValue::Text(self.to_string())
+    }
+}
+
+impl<T: ToSqlValue + ?Sized> ToSqlValue for &T {
+    fn to_sql_value(&self) -> SqlValue {
+        (**self).to_sql_value()
+    }
+}
+
+impl<T: ToSqlValue> ToSqlValue for Option<T> {
+    fn to_sql_value(&self) -> SqlValue {
+        match self {
+            Some(value) => value.to_sql_value(),
+            None => SqlValue::Null,
+        }
+    }
+}
+
+impl ToSqlValue for SqlValue {
+    fn to_sql_value(&self) -> SqlValue {
+        self.clone()
+    }
+}
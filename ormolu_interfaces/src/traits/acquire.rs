@@ -0,0 +1,19 @@
+use sqlx::{Acquire, Postgres};
+
+/// Anything a query can run on: a `&PgPool`, a `&mut PgConnection` or a
+/// `&mut Transaction<'_, Postgres>`.
+///
+/// Every method that talks to the database takes one, so the caller decides
+/// which pool or transaction it runs in:
+/// ```rust,ignore
+/// let mut tx = pool.begin().await?;
+/// let customer = Customer::get_by_email(&mut tx, "ann@example.com").await?;
+/// let orders = customer.orders().all(&mut tx).await?;
+/// tx.commit().await?;
+/// ```
+///
+/// [`transaction`](crate::transaction) does the same and commits or rolls
+/// back on its own.
+pub trait PgAcquire<'c>: Acquire<'c, Database = Postgres> + Send {}
+
+impl<'c, A> PgAcquire<'c> for A where A: Acquire<'c, Database = Postgres> + Send {}
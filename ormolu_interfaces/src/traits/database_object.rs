@@ -1,4 +1,5 @@
-use sqlx::{Pool, Postgres, postgres::PgPoolOptions};
+use crate::{DEFAULT_DATABASE, OrmoluError, database_pool};
+use sqlx::{Pool, Postgres};
 
 /// A database object is any defined object in a database that is used to store or manipulate data.
 ///
@@ -43,21 +44,27 @@ pub trait HasObjectName {
     fn object_name() -> &'static str;
 }
 
-// TODO: replace with dep injection in header #[gild(pool = get_cust_pool)]
 /// This trait defines how any database object can get its own ConnectionPool
+///
+/// By default that is the shared pool of [`GetConnectionPool::DATABASE`], see
+/// [`database_pool`]. Models of another database name it, and a model can
+/// bring its own pool with an `async fn() -> Result<PgPool, OrmoluError>`:
+/// ```rust,ignore
+/// #[derive(Table)]
+/// #[name = "public.page_view"]
+/// #[gild(database = "analytics")]
+/// pub struct PageView { .. }
+///
+/// #[derive(Table)]
+/// #[name = "public.customer"]
+/// #[gild(pool = crate::db::customer_pool)]
+/// pub struct Customer { .. }
+/// ```
 pub trait GetConnectionPool {
-    fn get_connection_pool() -> impl Future<Output = Pool<Postgres>> {
-        async {
-            dotenvy::dotenv().ok();
-
-            let database_url =
-                std::env::var("DATABASE_URL").expect("DATABASE_URL must be set in .env file");
+    /// Name of the database in the pool registry
+    const DATABASE: &'static str = DEFAULT_DATABASE;
 
-            PgPoolOptions::new()
-                .max_connections(5)
-                .connect(&database_url)
-                .await
-                .expect("Failed to create pool")
-        }
+    fn get_connection_pool() -> impl Future<Output = Result<Pool<Postgres>, OrmoluError>> + Send {
+        std::future::ready(database_pool(Self::DATABASE))
     }
 }
@@ -1,3 +1,6 @@
+mod acquire;
+pub use acquire::*;
+
 mod database_object;
 pub use database_object::*;
 
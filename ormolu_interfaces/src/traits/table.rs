@@ -1,6 +1,9 @@
-use super::DatabaseObject;
+use super::{DatabaseObject, PgAcquire};
 use crate::FilterState;
 use crate::OrmoluError;
+use crate::query::{ColRef, QueryState, Where};
+use crate::{SqlValue, ToSqlValue};
+use sqlx::{FromRow, postgres::PgRow};
 use std::{cell::RefCell, rc::Rc};
 
 /// Represents a database table.
@@ -10,6 +13,140 @@ pub trait Table
 where
     Self: DatabaseObject + Filterable + HasFields + Sized,
 {
+    /// Inserts the row and returns it as stored, with the values the database
+    /// generated for identity and defaulted columns.
+    fn insert<'c>(
+        &self,
+        db: impl PgAcquire<'c>,
+    ) -> impl Future<Output = Result<Self, OrmoluError>> + Send
+    where
+        Self: Insertable + for<'r> FromRow<'r, PgRow> + Send + Unpin,
+    {
+        let (sql, arguments) = QueryState::<Self>::new_insert(vec![self.insert_values()]).build();
+
+        async move {
+            let mut conn = db.acquire().await?;
+
+            Ok(sqlx::query_as_with::<_, Self, _>(sql.as_str(), arguments)
+                .fetch_one(&mut *conn)
+                .await?)
+        }
+    }
+
+    /// Inserts all rows in one transaction and returns them as stored, in the
+    /// same order.
+    ///
+    /// Postgres does not specify the order of the rows a multi-row INSERT
+    /// returns, so every row is sent in its own statement. Inside a
+    /// transaction the rows are inserted in a savepoint.
+    fn insert_many<'c>(
+        db: impl PgAcquire<'c>,
+        rows: &[Self],
+    ) -> impl Future<Output = Result<Vec<Self>, OrmoluError>> + Send
+    where
+        Self: Insertable + for<'r> FromRow<'r, PgRow> + Send + Unpin,
+    {
+        let statements: Vec<_> = rows
+            .iter()
+            .map(|row| QueryState::<Self>::new_insert(vec![row.insert_values()]).build())
+            .collect();
+
+        async move {
+            if statements.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let mut tx = db.begin().await?;
+            let mut inserted = Vec::with_capacity(statements.len());
+
+            for (sql, arguments) in statements {
+                inserted.push(
+                    sqlx::query_as_with::<_, Self, _>(sql.as_str(), arguments)
+                        .fetch_one(&mut *tx)
+                        .await?,
+                );
+            }
+
+            tx.commit().await?;
+            Ok(inserted)
+        }
+    }
+}
+
+/// The columns an INSERT writes and how to read their values off of a row.
+///
+/// `#[derive(Table)]` leaves out [`Identity`](crate::Identity) columns and
+/// fields marked `#[gild(default)]` so the database fills them in.
+pub trait Insertable {
+    /// Database names of the columns written by an INSERT.
+    fn insert_columns() -> &'static [&'static str];
+
+    /// The values of [`Insertable::insert_columns`], in the same order.
+    fn insert_values(&self) -> Vec<SqlValue>;
+}
+
+/// `Self` has a [`ForeignKey`](crate::ForeignKey) to `Other`.
+///
+/// `#[derive(Table)]` implements this on the table holding the foreign key,
+/// [`Joinable`] joins it from either side. A table with more than one foreign
+/// key to `Other`, or one to itself, has no impl and is joined on explicit
+/// columns with [`QuerySet::join_on`](crate::query::QuerySet::join_on).
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` has no single foreign key to `{Other}`",
+    note = "with several foreign keys to `{Other}`, or one to the table itself, name the columns explicitly"
+)]
+pub trait Related<Other: Table>: Table {
+    /// The columns that have to be equal, `(column of Self, column of Other)`.
+    fn join_columns() -> (ColRef, ColRef);
+}
+
+/// `Self` and `Other` can be joined on the foreign key between them, `Via`
+/// says which table holds it and is inferred where the join is written:
+/// ```rust,ignore
+/// let orders = orders.inner_join::<Customer, _>();
+/// let customers = customers.left_join::<Order, _>();
+/// ```
+/// When both tables reference each other the join is ambiguous and `Via`
+/// has to be named, [`OwnForeignKey`] or [`TheirForeignKey`].
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` and `{Other}` have no single foreign key between them",
+    note = "with several foreign keys to the same table, or one to the table itself, join with `QuerySet::join_on`"
+)]
+pub trait Joinable<Other: Table, Via>: Table {
+    /// The columns that have to be equal, `(column of Self, column of Other)`.
+    fn join_columns() -> (ColRef, ColRef);
+}
+
+/// [`Joinable`] on the foreign key of the table the join starts from.
+pub enum OwnForeignKey {}
+
+/// [`Joinable`] on the foreign key of the joined table.
+pub enum TheirForeignKey {}
+
+impl<T: Related<U>, U: Table> Joinable<U, OwnForeignKey> for T {
+    fn join_columns() -> (ColRef, ColRef) {
+        <T as Related<U>>::join_columns()
+    }
+}
+
+impl<T: Table, U: Related<T>> Joinable<U, TheirForeignKey> for T {
+    fn join_columns() -> (ColRef, ColRef) {
+        let (theirs, ours) = <U as Related<T>>::join_columns();
+        (ours, theirs)
+    }
+}
+
+/// The columns [`HasPrimaryKey::update`] writes back and how to read their
+/// values off of a row.
+///
+/// `#[derive(Table)]` includes every column but the primary key and
+/// [`Identity`](crate::Identity) columns.
+pub trait Updatable {
+    /// Database names of the columns written by an UPDATE.
+    fn update_columns() -> &'static [&'static str];
+
+    /// The values of [`Updatable::update_columns`], in the same order.
+    fn update_values(&self) -> Vec<SqlValue>;
 }
 
 /// Provides methods for working with keys of entities.
@@ -19,7 +156,10 @@ pub trait Key<Entity, T> {
     /// Asynchronously retrieves an entity by its key.
     ///
     /// Returns `Ok(Some(entity))` if found, `Ok(None)` if not found, or an error.
-    fn get_entity(&self) -> impl Future<Output = Result<Option<Entity>, OrmoluError>>;
+    fn get_entity<'c>(
+        &self,
+        db: impl PgAcquire<'c>,
+    ) -> impl Future<Output = Result<Option<Entity>, OrmoluError>> + Send;
 }
 
 /// Provides methods for working with primary keys in the context of a table.
@@ -32,10 +172,55 @@ where
     /// Returns a reference to the primary key of this record.
     fn primary_key(&self) -> &T;
 
+    /// Returns the database name of the primary key column.
+    fn primary_key_column() -> &'static str;
+
     /// Asynchronously retrieves a record by its primary key.
     ///
     /// Returns `Ok(Some(record))` if found, `Ok(None)` if not found, or an error.
-    fn get_by_primary_key(key: &T) -> impl Future<Output = Result<Option<Self>, OrmoluError>>;
+    fn get_by_primary_key<'c>(
+        db: impl PgAcquire<'c>,
+        key: &T,
+    ) -> impl Future<Output = Result<Option<Self>, OrmoluError>> + Send;
+
+    /// Writes every column of this record back to the row with the same
+    /// primary key, see [`Updatable`].
+    ///
+    /// Returns the number of rows that were updated, `0` when the row is gone.
+    fn update<'c>(
+        &self,
+        db: impl PgAcquire<'c>,
+    ) -> impl Future<Output = Result<u64, OrmoluError>> + Send
+    where
+        Self: Table + Updatable,
+        T: ToSqlValue,
+    {
+        let assignments = Self::update_columns()
+            .iter()
+            .copied()
+            .zip(self.update_values())
+            .collect();
+
+        let mut state = QueryState::<Self>::new_update(assignments);
+        state.and_where(Where::eq(Self::primary_key_column(), self.primary_key()));
+        state.execute(db)
+    }
+
+    /// Deletes the row with the same primary key as this record.
+    ///
+    /// Returns the number of rows that were deleted, `0` when the row is already gone.
+    fn delete<'c>(
+        &self,
+        db: impl PgAcquire<'c>,
+    ) -> impl Future<Output = Result<u64, OrmoluError>> + Send
+    where
+        Self: Table,
+        T: ToSqlValue,
+    {
+        let mut state = QueryState::<Self>::new_delete();
+        state.and_where(Where::eq(Self::primary_key_column(), self.primary_key()));
+        state.execute(db)
+    }
 }
 
 /// This uses some macro reflection to create a field mapping/lookup from the
@@ -80,6 +265,8 @@ pub trait Filterable {
     fn to_field_filter(state: Rc<RefCell<FilterState>>) -> Self::Proxy;
 }
 
+/// A struct of `bool`s with the same field names, for picking the columns a
+/// query selects, see [`QuerySet::select`](crate::sql_command::query::QuerySet::select).
 pub trait Selectable
 where
     Self::Select: Default,
@@ -87,4 +274,7 @@ where
     type Select;
 
     fn select() -> Self::Select;
+
+    /// Returns the names of the fields set to `true`, in declaration order.
+    fn selected_fields(select: &Self::Select) -> Vec<&'static str>;
 }
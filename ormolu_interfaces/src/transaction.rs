@@ -0,0 +1,64 @@
+use crate::{OrmoluError, PgAcquire};
+use futures::future::BoxFuture;
+use sqlx::{Postgres, Transaction};
+
+/// Runs `f` in a transaction on `db`, committed when `f` returns `Ok` and
+/// rolled back when it returns `Err`.
+///
+/// Every query method takes the `&mut Transaction` as its executor, and a
+/// `transaction` on it runs in a SAVEPOINT that only undoes its own changes.
+/// `f` boxes its future so that it can borrow the transaction:
+/// ```rust,ignore
+/// let order = transaction(&pool, |tx| {
+///     Box::pin(async move {
+///         let customer = Customer::get_by_email(&mut *tx, email)
+///             .await?
+///             .ok_or(OrmoluError::NotFound { table: "customer" })?;
+///         let order = Order::new(&customer, &cart).insert(&mut *tx).await?;
+///
+///         transaction(&mut *tx, |tx| {
+///             Box::pin(async move {
+///                 products()
+///                     .filter(|p| p.id.is_in(cart.product_ids()))
+///                     .update(&mut *tx, |p| p.status.set("reserved".to_string()))
+///                     .await
+///             })
+///         })
+///         .await?;
+///
+///         Ok::<_, OrmoluError>(order)
+///     })
+/// })
+/// .await?;
+/// ```
+///
+/// A panic in `f` drops the transaction while unwinding, which rolls it back
+/// as well. `f` may return any error that an [`OrmoluError`] converts into,
+/// a failed BEGIN or COMMIT is returned as one.
+// Not an `async fn`, its future has to be `Send` for `f` to await a nested
+// `transaction` inside its boxed future
+#[allow(clippy::manual_async_fn)]
+pub fn transaction<'c, A, F, T, E>(db: A, f: F) -> impl Future<Output = Result<T, E>> + Send
+where
+    A: PgAcquire<'c>,
+    F: for<'t> FnOnce(&'t mut Transaction<'c, Postgres>) -> BoxFuture<'t, Result<T, E>> + Send,
+    T: Send,
+    E: From<OrmoluError> + Send,
+{
+    async move {
+        let mut tx = db.begin().await.map_err(OrmoluError::from)?;
+
+        match f(&mut tx).await {
+            Ok(value) => {
+                tx.commit().await.map_err(OrmoluError::from)?;
+                Ok(value)
+            }
+            Err(error) => {
+                // The error of `f` is the one worth returning, a failed ROLLBACK
+                // is tried again when the transaction is dropped
+                tx.rollback().await.ok();
+                Err(error)
+            }
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use proc_macro2::TokenStream;
 use quote::quote;
+use syn::parse::{Parse, ParseStream};
 use syn::*;
 
 pub fn expand_derive_database_object(derive_input: &DeriveInput) -> syn::Result<TokenStream> {
@@ -7,8 +8,14 @@ pub fn expand_derive_database_object(derive_input: &DeriveInput) -> syn::Result<
     let mut object_name = String::new();
     let mut schema_name = String::new();
     let struct_name = &derive_input.ident;
+    let mut object_attributes = ObjectAttributes::default();
 
     for attr in derive_input.attrs.iter() {
+        if attr.path().is_ident("gild") {
+            object_attributes.merge(attr.parse_args()?);
+            continue;
+        }
+
         let Some(ident) = attr.path().get_ident() else {
             continue;
         };
@@ -46,7 +53,6 @@ pub fn expand_derive_database_object(derive_input: &DeriveInput) -> syn::Result<
             }
             "object" => object_name = litstr.value(),
             "schema" => schema_name = litstr.value(),
-            "get_pool" => todo!(),
             aname => {
                 return Err(Error::new_spanned(
                     derive_input,
@@ -71,6 +77,7 @@ pub fn expand_derive_database_object(derive_input: &DeriveInput) -> syn::Result<
     }
 
     let qualified_name = format!("{schema_name}.{object_name}");
+    let connection_pool = object_attributes.expand_get_connection_pool();
 
     Ok(quote! {
         // TODO: HasName -> HasObjectName
@@ -95,7 +102,77 @@ pub fn expand_derive_database_object(derive_input: &DeriveInput) -> syn::Result<
             }
         }
 
-        impl ormolu_interfaces::GetConnectionPool for #struct_name {}
+        impl ormolu_interfaces::GetConnectionPool for #struct_name {
+            #connection_pool
+        }
+
         impl ormolu_interfaces::DatabaseObject for #struct_name {}
     })
 }
+
+/// The `#[gild(...)]` attributes on the struct itself.
+#[derive(Default)]
+struct ObjectAttributes {
+    /// Name of the database in the pool registry
+    database: Option<LitStr>,
+    /// `async fn() -> Result<PgPool, OrmoluError>` used instead of the registry
+    pool: Option<Path>,
+}
+
+impl ObjectAttributes {
+    fn merge(&mut self, other: ObjectAttributes) {
+        self.database = other.database.or(self.database.take());
+        self.pool = other.pool.or(self.pool.take());
+    }
+
+    fn expand_get_connection_pool(&self) -> TokenStream {
+        let database = self.database.as_ref().map(|database| {
+            quote! {
+                const DATABASE: &'static str = #database;
+            }
+        });
+
+        let pool = self.pool.as_ref().map(|pool| {
+            quote! {
+                fn get_connection_pool() -> impl Future<
+                    Output = Result<sqlx::Pool<sqlx::Postgres>, ormolu_interfaces::OrmoluError>,
+                > + Send {
+                    #pool()
+                }
+            }
+        });
+
+        quote! {
+            #database
+            #pool
+        }
+    }
+}
+
+impl Parse for ObjectAttributes {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut attributes = ObjectAttributes::default();
+
+        while !input.is_empty() {
+            let ident: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+
+            match ident.to_string().as_str() {
+                "database" => attributes.database = Some(input.parse()?),
+                "pool" => attributes.pool = Some(input.parse()?),
+                _ => {
+                    return Err(Error::new_spanned(
+                        ident,
+                        "unknown gild attribute, expected `database` or `pool`",
+                    ))
+                }
+            }
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(attributes)
+    }
+}
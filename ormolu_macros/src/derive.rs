@@ -1,4 +1,8 @@
-use crate::{database_object::expand_derive_database_object, table_metadata::expand_derive_table};
+use crate::{
+    database_object::expand_derive_database_object,
+    table_metadata::{expand_derive_table, TableMetadata},
+    utils::replace_self,
+};
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
@@ -21,16 +25,16 @@ pub fn table(derive_input: &DeriveInput) -> TokenStream {
         Err(e) => return e.into_compile_error().into(),
     };
 
-    let from_row = match expand_derive_from_row(derive_input) {
-        Ok(ts) => ts,
-        Err(e) => return e.into_compile_error().into(),
-    };
-
     let table_metadata = match expand_derive_table(derive_input) {
         Ok(t) => t,
         Err(err) => return err.into_compile_error().into(),
     };
 
+    let from_row = match expand_derive_from_row(&with_sqlx_renames(derive_input, &table_metadata)) {
+        Ok(ts) => ts,
+        Err(e) => return e.into_compile_error().into(),
+    };
+
     let table = table_metadata.to_token_stream();
 
     quote! {
@@ -43,6 +47,28 @@ pub fn table(derive_input: &DeriveInput) -> TokenStream {
     .into()
 }
 
+/// Adds `#[sqlx(rename = "...")]` to the renamed fields so `FromRow` reads
+/// them from the database column.
+fn with_sqlx_renames(derive_input: &DeriveInput, table_metadata: &TableMetadata) -> DeriveInput {
+    let mut derive_input = derive_input.clone();
+
+    if let Data::Struct(DataStruct {
+        fields: Fields::Named(fields),
+        ..
+    }) = &mut derive_input.data
+    {
+        for (field, column) in fields.named.iter_mut().zip(&table_metadata.fields) {
+            if let Some(column_name) = &column.attributes.column_name {
+                field
+                    .attrs
+                    .push(parse_quote! { #[sqlx(rename = #column_name)] });
+            }
+        }
+    }
+
+    derive_input
+}
+
 pub fn stored_procedure(derive_input: &DeriveInput) -> TokenStream {
     let dbo_impl = match expand_derive_database_object(derive_input) {
         Ok(ts) => ts,
@@ -113,7 +139,8 @@ pub fn filterable(derive_input: &DeriveInput) -> TokenStream {
 
     let proxy_fields = fields.iter().map(|field| {
         let name = &field.ident;
-        let ty = &field.ty;
+        // `Self` in the proxy would be the proxy, not the table
+        let ty = replace_self(&field.ty, struct_name);
         quote! {
             pub #name: ormolu_interfaces::field::Col<#ty>
         }
@@ -123,7 +150,11 @@ pub fn filterable(derive_input: &DeriveInput) -> TokenStream {
         let name = &field.ident;
         let field_name = name.as_ref().unwrap().to_string();
         quote! {
-            #name: Col::new(#field_name, state.clone())
+            #name: Col::new(
+                table,
+                <#struct_name as ormolu_interfaces::HasFields>::get_db_column_name(#field_name),
+                state.clone(),
+            )
         }
     });
 
@@ -134,6 +165,7 @@ pub fn filterable(derive_input: &DeriveInput) -> TokenStream {
 
         impl #filter_name {
             pub fn new(state: std::rc::Rc<std::cell::RefCell<ormolu_interfaces::field::filter::FilterState>>) -> Self {
+                let table = <#struct_name as ormolu_interfaces::HasQualifiedName>::qualified_name();
                 Self {
                     #( #proxy_inits, )*
                 }
@@ -169,6 +201,16 @@ pub fn selectable(derive_input: &DeriveInput) -> TokenStream {
         }
     });
 
+    let selected = fields.iter().map(|field| {
+        let name = &field.ident;
+        let field_name = name.as_ref().unwrap().to_string();
+        quote! {
+            if select.#name {
+                fields.push(#field_name);
+            }
+        }
+    });
+
     quote! {
         #[derive(Default)]
         pub struct #select_name {
@@ -180,10 +222,19 @@ pub fn selectable(derive_input: &DeriveInput) -> TokenStream {
 
             /// Returns a struct for picking specific fields to select.
             ///
-            /// TODO: select doc comment example
+            /// ```rust,ignore
+            /// let mut select = Customer::select();
+            /// select.email = true;
+            /// ```
             fn select() -> Self::Select {
                 #select_name::default()
             }
+
+            fn selected_fields(select: &Self::Select) -> Vec<&'static str> {
+                let mut fields = Vec::new();
+                #( #selected )*
+                fields
+            }
         }
     }
     .into()
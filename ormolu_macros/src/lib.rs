@@ -1,15 +1,51 @@
 mod database_object;
 mod derive;
+mod query;
 mod table_metadata;
 mod utils;
 
 use proc_macro::TokenStream;
 use syn::*;
 
-// TODO: Use the filter at compile time, since we know it maps to actual db tables
-// the filter just creates the sql so there is no reason it has to run at
-// runtime we just need something to actually create the query before the filter
-// logic runs. macro will do that
+/// Builds a query whose SQL is only rendered once, the first time it runs.
+///
+/// Takes a table and a chain of [`QuerySet`] methods and evaluates to a
+/// `StaticQuery` holding the statement as a `&'static str` and the values bound
+/// to it on this run. The SQL is not generated during expansion, the chain is
+/// run as a `QuerySet` the first time the call site runs and its statement is
+/// kept for every later run:
+/// ```rust,ignore
+/// let q = query!(Order
+///     .filter(|o| o.status == status && o.total_amount > min_total)
+///     .order_by_desc(|o| o.created_at));
+///
+/// let orders = q.all().await?;
+/// ```
+///
+/// Values that do not depend on the closure arguments, `status` and
+/// `min_total` above, become bind parameters that are evaluated on every run,
+/// so the closures are never traced again. They are still type checked against
+/// the columns they are compared to, and so are the field names.
+///
+/// Since the statement cannot change between runs `&&`, `||` and `!` can only
+/// combine conditions on columns and method arguments have to be literals or
+/// closures. That includes the arguments of column methods, a list only known
+/// at runtime as in `o.id.is_in(&ids)` is refused while `o.id.is_in(&[1, 2])`
+/// is part of the statement. The table cannot be a type parameter either,
+/// every instance of a generic function would share one statement. Anything
+/// else is a compile error, use a [`QuerySet`] for queries that are built at
+/// runtime.
+///
+/// [`QuerySet`]: ../ormolu_interfaces/sql_command/query/struct.QuerySet.html
+#[proc_macro]
+pub fn query(input: TokenStream) -> TokenStream {
+    let chain = parse_macro_input!(input as Expr);
+    match query::expand_query(chain) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
 #[proc_macro]
 pub fn create_select(input: TokenStream) -> TokenStream {
     // Parse the input tokens into an identifier
@@ -44,7 +80,7 @@ pub fn derive_view(input: TokenStream) -> TokenStream {
     derive::view(&derive_input)
 }
 
-#[proc_macro_derive(DatabaseObject, attributes(name, schema, object))]
+#[proc_macro_derive(DatabaseObject, attributes(name, schema, object, gild))]
 pub fn derive_database_object(input: TokenStream) -> TokenStream {
     let derive_input = parse_macro_input!(input as DeriveInput);
     derive::database_object(&derive_input)
@@ -0,0 +1,257 @@
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::spanned::Spanned;
+use syn::*;
+
+/// Expands `query!(Table.filter(..).order_by_desc(..))`.
+///
+/// The chain is built into a `QuerySet` the first time the call site runs and
+/// rendered into a static statement, nothing is rendered during expansion.
+/// Every value in a closure that does not depend on the
+/// closure's arguments is replaced with a `Slot`, which keeps its type so the
+/// comparison is still checked, and is bound again on every run.
+pub fn expand_query(chain: Expr) -> Result<TokenStream> {
+    let mut chain = chain;
+    let mut slots = Slots::default();
+
+    let mut call = &mut chain;
+    let table = loop {
+        match call {
+            Expr::MethodCall(method_call) => {
+                for arg in &mut method_call.args {
+                    slots.argument(arg)?;
+                }
+                call = &mut method_call.receiver;
+            }
+            Expr::Path(table) => {
+                let table = table.clone();
+                *call = parse_quote! {
+                    ormolu_interfaces::sql_command::query::QuerySet::new(
+                        ormolu_interfaces::sql_command::query::QueryState::<#table>::new_select()
+                    )
+                };
+                break table;
+            }
+            other => {
+                return Err(Error::new_spanned(
+                    other,
+                    "expected a table followed by QuerySet methods, `Order.filter(|o| ...)`",
+                ))
+            }
+        }
+    };
+
+    // The statement is shared by every instance of a generic function, a type
+    // parameter as the table would render the first one's SQL for all of them.
+    // Naming it in a nested item makes it a compile error.
+    let table_check = quote_spanned! { table.span() =>
+        const _: ::std::marker::PhantomData<#table> = ::std::marker::PhantomData;
+    };
+
+    let slot_names: Vec<_> = (0..slots.values.len()).map(slot_name).collect();
+    let slot_values = &slots.values;
+    let statement = Ident::new("STATEMENT", Span::mixed_site());
+
+    Ok(quote! {
+        {
+            #table_check
+            #( let #slot_names = &(#slot_values); )*
+
+            static #statement: ::std::sync::OnceLock<ormolu_interfaces::sql_command::query::PreparedStatement> =
+                ::std::sync::OnceLock::new();
+
+            ormolu_interfaces::sql_command::query::StaticQuery::new(
+                &#statement,
+                || #chain,
+                vec![#( ormolu_interfaces::ToSqlValue::to_sql_value(#slot_names) ),*],
+            )
+        }
+    })
+}
+
+fn slot_name(index: usize) -> Ident {
+    format_ident!("slot_{}", index, span = Span::mixed_site())
+}
+
+#[derive(Default)]
+struct Slots {
+    /// Arguments of the closures that are being rewritten
+    params: Vec<Ident>,
+    /// The expression of every slot, evaluated on every run
+    values: Vec<Expr>,
+}
+
+impl Slots {
+    /// A method argument, a closure or a literal that is part of the statement.
+    fn argument(&mut self, arg: &mut Expr) -> Result<()> {
+        match arg {
+            Expr::Closure(closure) => self.closure(closure),
+            arg if self.depends_on_params(arg) => self.rewrite(arg),
+            arg if is_literal(arg) => Ok(()),
+            arg => Err(Error::new_spanned(
+                arg,
+                "query! only takes closures and literals as arguments since the statement is built once, \
+                 compare a column to this value instead",
+            )),
+        }
+    }
+
+    /// An argument of a method called on a column, `is_in` or `contains`, which
+    /// is written into the statement and cannot be a slot.
+    fn method_argument(&mut self, method: &Ident, arg: &mut Expr) -> Result<()> {
+        match arg {
+            Expr::Closure(closure) => self.closure(closure),
+            arg if self.depends_on_params(arg) => self.rewrite(arg),
+            arg if is_literal(arg) => Ok(()),
+            arg => Err(Error::new_spanned(
+                arg,
+                format!(
+                    "the argument of `{method}` is part of the statement, query! only takes a literal here, \
+                     build a QuerySet at runtime for values only known then"
+                ),
+            )),
+        }
+    }
+
+    fn closure(&mut self, closure: &mut ExprClosure) -> Result<()> {
+        let params = closure.inputs.len();
+        for input in &closure.inputs {
+            let ident = match input {
+                Pat::Ident(pat) => &pat.ident,
+                Pat::Type(PatType { pat, .. }) => match &**pat {
+                    Pat::Ident(pat) => &pat.ident,
+                    pat => return Err(Error::new_spanned(pat, "expected a closure argument name")),
+                },
+                pat => return Err(Error::new_spanned(pat, "expected a closure argument name")),
+            };
+            self.params.push(ident.clone());
+        }
+
+        let rewritten = self.rewrite(&mut closure.body);
+        self.params.truncate(self.params.len() - params);
+        rewritten
+    }
+
+    /// Replaces the values in an expression on the closure arguments with slots.
+    fn rewrite(&mut self, expr: &mut Expr) -> Result<()> {
+        if !self.depends_on_params(expr) {
+            if !is_literal(expr) {
+                let index = self.values.len();
+                let name = slot_name(index);
+                self.values.push(expr.clone());
+                *expr = parse_quote! {
+                    ormolu_interfaces::sql_command::query::Slot::of(#index, #name)
+                };
+            }
+            return Ok(());
+        }
+
+        match expr {
+            Expr::Binary(binary) => match binary.op {
+                BinOp::And(_) | BinOp::Or(_) => {
+                    self.condition(&mut binary.left)?;
+                    self.condition(&mut binary.right)
+                }
+                _ => {
+                    self.rewrite(&mut binary.left)?;
+                    self.rewrite(&mut binary.right)
+                }
+            },
+            Expr::Unary(unary) => match unary.op {
+                UnOp::Not(_) => self.condition(&mut unary.expr),
+                _ => self.rewrite(&mut unary.expr),
+            },
+            Expr::Paren(paren) => self.rewrite(&mut paren.expr),
+            Expr::Group(group) => self.rewrite(&mut group.expr),
+            Expr::Reference(reference) => self.rewrite(&mut reference.expr),
+            Expr::Field(field) => self.rewrite(&mut field.base),
+            Expr::Path(_) => Ok(()),
+            Expr::MethodCall(method_call) => {
+                self.rewrite(&mut method_call.receiver)?;
+                for arg in &mut method_call.args {
+                    self.method_argument(&method_call.method, arg)?;
+                }
+                Ok(())
+            }
+            Expr::Closure(closure) => self.closure(closure),
+            Expr::Tuple(tuple) => {
+                for elem in &mut tuple.elems {
+                    self.rewrite(elem)?;
+                }
+                Ok(())
+            }
+            Expr::Assign(assign) => {
+                self.rewrite(&mut assign.left)?;
+                self.argument(&mut assign.right)
+            }
+            Expr::Block(block) => {
+                for stmt in &mut block.block.stmts {
+                    match stmt {
+                        Stmt::Expr(expr, _) => self.rewrite(expr)?,
+                        stmt => {
+                            return Err(Error::new_spanned(
+                                stmt,
+                                "query! closures can only contain expressions",
+                            ))
+                        }
+                    }
+                }
+                Ok(())
+            }
+            expr => Err(Error::new_spanned(
+                expr,
+                "not supported by query!, build a QuerySet at runtime instead",
+            )),
+        }
+    }
+
+    /// An operand of `&&`, `||` or `!`, which has to be a condition on the
+    /// closure arguments so the statement does not change between runs.
+    fn condition(&mut self, expr: &mut Expr) -> Result<()> {
+        if !self.depends_on_params(expr) && !is_literal(expr) {
+            return Err(Error::new_spanned(
+                expr,
+                "query! conditions cannot depend on runtime values since the statement is built once",
+            ));
+        }
+
+        self.rewrite(expr)
+    }
+
+    fn depends_on_params(&self, expr: &Expr) -> bool {
+        mentions(expr.to_token_stream(), &self.params)
+    }
+}
+
+/// Whether any of the identifiers is used in the tokens, identifiers after a
+/// `.` are field or method names and never count.
+fn mentions(tokens: TokenStream, idents: &[Ident]) -> bool {
+    let mut after_dot = false;
+    for token in tokens {
+        match &token {
+            TokenTree::Ident(ident) if !after_dot && idents.contains(ident) => return true,
+            TokenTree::Group(group) if mentions(group.stream(), idents) => return true,
+            _ => {}
+        }
+        after_dot = matches!(&token, TokenTree::Punct(punct) if punct.as_char() == '.');
+    }
+
+    false
+}
+
+/// Literals are part of the statement like they are for a `QuerySet`.
+fn is_literal(expr: &Expr) -> bool {
+    match expr {
+        Expr::Lit(_) => true,
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+            ..
+        })
+        | Expr::Reference(ExprReference { expr, .. })
+        | Expr::Paren(ExprParen { expr, .. })
+        | Expr::Group(ExprGroup { expr, .. }) => is_literal(expr),
+        Expr::Array(array) => array.elems.iter().all(is_literal),
+        _ => false,
+    }
+}
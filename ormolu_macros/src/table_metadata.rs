@@ -47,9 +47,7 @@ impl<'de> TableMetadata<'de> {
             Span::call_site(),
         );
 
-        let field_names: Vec<String> = self.fields.iter().map(|f| f.name.to_string()).collect();
-        let field_strs: Vec<proc_macro2::TokenStream> =
-            field_names.iter().map(|name| quote! { #name }).collect();
+        let column_names: Vec<String> = self.fields.iter().map(|f| f.column_name()).collect();
         quote! {
             static #exceptions_name: phf::Map<&'static str, &'static str> = phf::phf_map! {
                 #(#exceptions),*
@@ -62,7 +60,7 @@ impl<'de> TableMetadata<'de> {
                 }
 
                 fn database_columns() -> &'static [&'static str] {
-                    &[#(#field_strs),*]
+                    &[#(#column_names),*]
                 }
             }
         }
@@ -87,12 +85,15 @@ impl<'de> TableMetadata<'de> {
                     &self.#field_name
                 }
 
-                // fn primary_key_field_name() -> &'static str {
-                //     stringify!(#field_name)
-                // }
+                fn primary_key_column() -> &'static str {
+                    <Self as ormolu_interfaces::HasFields>::get_db_column_name(stringify!(#field_name))
+                }
 
-                fn get_by_primary_key(key: &#field_type) -> impl Future<Output = Result<Option<Self>, ormolu_interfaces::OrmoluError>> {
-                    Self::#method_name(*key)
+                fn get_by_primary_key<'c>(
+                    db: impl ormolu_interfaces::PgAcquire<'c>,
+                    key: &#field_type,
+                ) -> impl Future<Output = Result<Option<Self>, ormolu_interfaces::OrmoluError>> + Send {
+                    Self::#method_name(db, *key)
                 }
             }
         }
@@ -112,24 +113,28 @@ impl<'de> TableMetadata<'de> {
 
         let struct_name = &self.struct_name;
         let method_name = Ident::new(format!("get_by_{field_name}").as_str(), Span::call_site());
-        let sql_value_string = generate_field_to_string_expr(field_name, &field_type);
+        let sql_value = generate_field_to_sql_value_expr(field_name, &field_type);
         quote! {
             impl #struct_name {
                 // TODO: make type a ref?
-                fn #method_name(#field_name: #field_type) -> impl Future<Output = Result<Option<Self>, ormolu_interfaces::OrmoluError>> {
+                fn #method_name<'c>(
+                    db: impl ormolu_interfaces::PgAcquire<'c>,
+                    #field_name: #field_type,
+                ) -> impl Future<Output = Result<Option<Self>, ormolu_interfaces::OrmoluError>> + Send {
                     // TODO: this feels like it is slightly poorly designed
-                    async move {
-                        let mut q: ormolu_interfaces::sql_command::query::QueryState<Self> = ormolu_interfaces::sql_command::query::QueryState::new_select();
-                        let s: String = #sql_value_string;
-                        let db_col_name = Self::get_db_column_name(stringify!(#field_name));
-                        let where_cond = ormolu_interfaces::sql_command::query::where_cond::Where::eq(db_col_name, s);
-                        q.where_conditions.push(where_cond);
+                    let mut q: ormolu_interfaces::sql_command::query::QueryState<Self> = ormolu_interfaces::sql_command::query::QueryState::new_select();
+                    let value: ormolu_interfaces::SqlValue = #sql_value;
+                    let db_col_name = Self::get_db_column_name(stringify!(#field_name));
+                    let where_cond = ormolu_interfaces::sql_command::query::where_cond::Where::eq(db_col_name, value);
+                    q.and_where(where_cond);
+                    let (sql, arguments) = q.build();
 
-                        let pool = Self::get_connection_pool().await;
+                    async move {
+                        let mut conn = sqlx::Acquire::acquire(db).await?;
 
                         Ok(
-                            sqlx::query_as::<_, Self>(q.to_string().as_str())
-                                .fetch_optional(&pool)
+                            sqlx::query_as_with::<_, Self, _>(sql.as_str(), arguments)
+                                .fetch_optional(&mut *conn)
                                 .await?,
                         )
                     }
@@ -138,6 +143,56 @@ impl<'de> TableMetadata<'de> {
         }
     }
 
+    /// Database column names and field names of the columns matching `include`.
+    fn written_columns(
+        &self,
+        include: impl Fn(&ColumnMetadata) -> bool,
+    ) -> (Vec<String>, Vec<&Ident>) {
+        self.fields
+            .iter()
+            .filter(|col| include(col))
+            .map(|col| (col.column_name(), &col.name))
+            .unzip()
+    }
+
+    #[inline]
+    fn expanded_insertable(&self) -> TokenStream2 {
+        let struct_name = &self.struct_name;
+        let (columns, fields) =
+            self.written_columns(|col| !col.attributes.default && !is_identity(&col.ty));
+
+        quote! {
+            impl ormolu_interfaces::Insertable for #struct_name {
+                fn insert_columns() -> &'static [&'static str] {
+                    &[#(#columns),*]
+                }
+
+                fn insert_values(&self) -> Vec<ormolu_interfaces::SqlValue> {
+                    vec![#(ormolu_interfaces::ToSqlValue::to_sql_value(&self.#fields)),*]
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn expanded_updatable(&self) -> TokenStream2 {
+        let struct_name = &self.struct_name;
+        let (columns, fields) =
+            self.written_columns(|col| !col.constraints.primary_key && !is_identity(&col.ty));
+
+        quote! {
+            impl ormolu_interfaces::Updatable for #struct_name {
+                fn update_columns() -> &'static [&'static str] {
+                    &[#(#columns),*]
+                }
+
+                fn update_values(&self) -> Vec<ormolu_interfaces::SqlValue> {
+                    vec![#(ormolu_interfaces::ToSqlValue::to_sql_value(&self.#fields)),*]
+                }
+            }
+        }
+    }
+
     #[inline]
     fn expanded_impls(&self) -> TokenStream2 {
         let struct_name = &self.struct_name;
@@ -154,6 +209,10 @@ impl<'de> TableMetadata<'de> {
 
         let other_model = ident;
         let struct_name = &self.struct_name;
+        if self.foreign_keys_to(other_model) > 1 {
+            return TokenStream2::new();
+        }
+
         let singular = Ident::new(
             other_model
                 .to_string()
@@ -196,16 +255,73 @@ impl<'de> TableMetadata<'de> {
         }
     }
 
+    /// Lets the table be joined on its foreign key, the other direction comes
+    /// from the blanket impl of `Joinable`.
+    ///
+    /// Tables with more than one foreign key to the same table, or one to
+    /// themselves, get no impl since the join would be ambiguous or need an
+    /// alias. Those are joined on explicit columns.
+    #[inline]
+    fn expanded_related(&self, field: &ColumnMetadata) -> TokenStream2 {
+        let Some((other_model, ord)) = &field.constraints.foreign_key else {
+            return TokenStream2::new();
+        };
+
+        let struct_name = &self.struct_name;
+        if other_model == *struct_name || other_model == "Self" {
+            return TokenStream2::new();
+        }
+
+        if self.foreign_keys_to(other_model) > 1 {
+            return TokenStream2::new();
+        }
+
+        let field_name = &field.name;
+
+        quote! {
+            impl ormolu_interfaces::Related<#other_model> for #struct_name {
+                fn join_columns() -> (ormolu_interfaces::sql_command::query::ColRef, ormolu_interfaces::sql_command::query::ColRef) {
+                    (
+                        ormolu_interfaces::sql_command::query::ColRef::new(
+                            <#struct_name as ormolu_interfaces::HasQualifiedName>::qualified_name(),
+                            <#struct_name as ormolu_interfaces::HasFields>::get_db_column_name(stringify!(#field_name)),
+                        ),
+                        ormolu_interfaces::sql_command::query::ColRef::new(
+                            <#other_model as ormolu_interfaces::HasQualifiedName>::qualified_name(),
+                            <#other_model as ormolu_interfaces::HasFields>::column(#ord)
+                                .expect("foreign key ordinal is past the last column of the referenced table"),
+                        ),
+                    )
+                }
+            }
+        }
+    }
+
+    /// How many columns reference `model`, with more than one it is ambiguous
+    /// which one a relation follows.
+    fn foreign_keys_to(&self, model: &Ident) -> usize {
+        self.fields
+            .iter()
+            .filter(|f| matches!(&f.constraints.foreign_key, Some((other, _)) if other == model))
+            .count()
+    }
+
     pub fn to_token_stream(&self) -> TokenStream2 {
         let mut stream = TokenStream2::new();
 
-        stream.append_all([self.expanded_reflection(), self.expanded_impls()]);
+        stream.append_all([
+            self.expanded_reflection(),
+            self.expanded_insertable(),
+            self.expanded_updatable(),
+            self.expanded_impls(),
+        ]);
 
         for field in &self.fields {
             stream.append_all([
                 self.expanded_primary_key(field),
                 self.expanded_unique(field),
                 self.expanded_references(field),
+                self.expanded_related(field),
             ]);
         }
 
@@ -247,6 +363,15 @@ impl ColumnMetadata {
             constraints,
         }
     }
+
+    /// Name of the database column, the field name unless it is renamed with
+    /// `#[gild(column = "...")]`.
+    pub fn column_name(&self) -> String {
+        self.attributes
+            .column_name
+            .clone()
+            .unwrap_or_else(|| self.name.to_string())
+    }
 }
 
 pub fn expand_derive_table(derive_input: &DeriveInput) -> Result<TableMetadata> {
@@ -286,12 +411,6 @@ pub fn expand_derive_table(derive_input: &DeriveInput) -> Result<TableMetadata>
             CustomType::Other(_) => {}
         }
 
-        col.constraints = ColumnConstraints {
-            primary_key,
-            unique: primary_key || unique,
-            foreign_key,
-        };
-
         for attr in &field.attrs {
             if attr.path().is_ident("gild") {
                 if let Ok(attributes) = attr.parse_args::<FieldAttributes>() {
@@ -300,6 +419,15 @@ pub fn expand_derive_table(derive_input: &DeriveInput) -> Result<TableMetadata>
             }
         }
 
+        primary_key |= col.attributes.primary_key;
+        unique |= col.attributes.unique;
+
+        col.constraints = ColumnConstraints {
+            primary_key,
+            unique: primary_key || unique,
+            foreign_key,
+        };
+
         table.fields.push(col);
     }
 
@@ -311,6 +439,12 @@ pub fn expand_derive_table(derive_input: &DeriveInput) -> Result<TableMetadata>
 pub struct FieldAttributes {
     /// Actual database column name
     pub column_name: Option<String>,
+    /// The database has a default for the column, it is left out of an INSERT
+    pub default: bool,
+    /// Same as a `PrimaryKey<Self, T>` field, for columns of a plain type
+    pub primary_key: bool,
+    /// Same as a `Unique<T>` field, for columns of a plain type
+    pub unique: bool,
 }
 
 impl Parse for FieldAttributes {
@@ -324,13 +458,15 @@ impl Parse for FieldAttributes {
                 let ident: Ident = input.parse()?;
 
                 match ident.to_string().as_str() {
-                    // "primary_key" => {
-                    //     constraints.unique = true;
-                    //     constraints.primary_key = true;
-                    // }
-                    // "unique" => {
-                    //     constraints.unique = true;
-                    // }
+                    "primary_key" => {
+                        constraints.primary_key = true;
+                    }
+                    "unique" => {
+                        constraints.unique = true;
+                    }
+                    "default" => {
+                        constraints.default = true;
+                    }
                     "column" => {
                         input.parse::<Token![=]>()?;
                         let expr: Expr = input.parse()?;
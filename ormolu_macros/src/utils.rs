@@ -54,45 +54,68 @@ pub fn unwrap_unique_or_self(ty: &Type) -> &Type {
     ty
 }
 
-pub fn generate_field_to_string_expr(field: &Ident, ty: &Type) -> TokenStream {
+/// If the type is `Identity<T>`, return `T`. Otherwise, return the original type.
+pub fn unwrap_identity_or_self(ty: &Type) -> &Type {
+    if let Type::Path(type_path) = ty {
+        if type_path.qself.is_none() {
+            if let Some(segment) = type_path.path.segments.last() {
+                if segment.ident == "Identity" {
+                    if let PathArguments::AngleBracketed(ref args) = segment.arguments {
+                        if let Some(GenericArgument::Type(inner_ty)) = args.args.first() {
+                            return inner_ty;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    ty
+}
+
+/// Replaces `Self` in a field type with the name of the struct, so the type
+/// means the same thing when it is used in another struct like the proxy.
+pub fn replace_self(ty: &Type, struct_name: &Ident) -> Type {
+    let mut ty = ty.clone();
+    if let Type::Path(type_path) = &mut ty {
+        if type_path.qself.is_none() && type_path.path.is_ident("Self") {
+            return parse_quote! { #struct_name };
+        }
+
+        for segment in &mut type_path.path.segments {
+            if let PathArguments::AngleBracketed(args) = &mut segment.arguments {
+                for arg in &mut args.args {
+                    if let GenericArgument::Type(inner) = arg {
+                        *inner = replace_self(inner, struct_name);
+                    }
+                }
+            }
+        }
+    }
+
+    ty
+}
+
+/// Whether the column is generated by the database, `Identity<T>` or `Option<Identity<T>>`.
+pub fn is_identity(ty: &Type) -> bool {
+    matches!(
+        unwrap_option_or_self(ty),
+        Type::Path(type_path) if type_path.path.segments.last().is_some_and(|s| s.ident == "Identity")
+    )
+}
+
+/// Creates the expression that turns a `get_by_*` argument into a bound `SqlValue`.
+pub fn generate_field_to_sql_value_expr(field: &Ident, ty: &Type) -> TokenStream {
     match ty {
         Type::ImplTrait(TypeImplTrait { .. }) => {
-            // let is_into_string = bounds.iter().any(|b| {
-            //     if let TypeParamBound::Trait(tb) = b {
-            //         tb.path
-            //             .segments
-            //             .last()
-            //             .map_or(false, |seg| seg.ident == "Into")
-            //             && tb.path.to_token_stream().to_string().contains("String")
-            //     } else {
-            //         false
-            //     }
-            // });
-
-            // if is_into_string {
-            //     return quote! {
-            //         #field.into()
-            //     };
-            // }
-
+            // `impl Into<String>` has to be converted before it can be bound
             quote! {
-                #field.into()
+                ormolu_interfaces::ToSqlValue::to_sql_value(&Into::<String>::into(#field))
             }
         }
-
-        // Type::Path(tp) => {
-        //     if let Some(seg) = tp.path.segments.last() {
-        //         if seg.ident == "String" || seg.ident == "str" {
-        //             return quote! {
-        //                 #field.into()
-        //             };
-        //         }
-        //     }
-        // }
         _ => {
-            // Fallback for primitives
             quote! {
-                #field.to_string()
+                ormolu_interfaces::ToSqlValue::to_sql_value(&#field)
             }
         }
     }
@@ -108,7 +131,7 @@ pub enum CustomType {
 }
 
 pub fn parse_custom_type(mut ty: &Type) -> CustomType {
-    ty = unwrap_option_or_self(ty);
+    ty = unwrap_identity_or_self(unwrap_option_or_self(ty));
     if let Type::Path(type_path) = ty {
         let segment = match type_path.path.segments.last() {
             Some(seg) => seg,